@@ -0,0 +1,154 @@
+//! Compares `BitVecReader` with `CachedBitReader` on the same entity snapshot, and batch reads and writes of uint
+//! arrays with reading and writing them one at a time. Runs on stable with `cargo bench`.
+
+use std::{
+	hint::black_box,
+	time::{Duration, Instant},
+};
+
+use bitvecbuf::{
+	bitvec::{field::BitField, slice::BitSlice, vec::BitVec},
+	BitSliceReader, BitVecReader, BitVecWriter, CacheOrder, CachedBitReader, LoadBits, Lsb0, Msb0,
+	StoreBits,
+};
+
+const ENTITIES: u32 = 1000;
+const SAMPLES: usize = 64 * 64;
+
+/// Writes a snapshot of entities with a mix of small fields, coordinates and varints, like a game server would.
+fn snapshot<O: CacheOrder>() -> Vec<u8>
+where
+	BitSlice<O, u8>: BitField + StoreBits<O>,
+{
+	let mut writer = BitVecWriter::<O>::new(BitVec::new());
+	for i in 0..ENTITIES {
+		writer.write_uint(i % 2048, 11);
+		writer.write_bit(i % 3 == 0);
+		writer.write_ubitvar(i * 7);
+		writer.write_int(-((i % 64) as i8), 7);
+		writer.write_bit_vec3_coord([i as f32 * 0.5, -(i as f32), 0.]);
+		writer.write_uint(i as u64 * 0x1_0001, 40);
+		writer.write_varint32(i * 300);
+		writer.write_float(i as f32 / 3.);
+	}
+	writer.into_bytes()
+}
+
+macro_rules! decode {
+	( $reader:expr ) => {{
+		let reader = &mut $reader;
+		let mut sum = 0_u64;
+		for _ in 0..ENTITIES {
+			sum += reader.read_uint::<u32>(11).unwrap() as u64;
+			sum += reader.read_bit().unwrap() as u64;
+			sum += reader.read_ubitvar().unwrap() as u64;
+			sum += reader.read_int::<i8>(7).unwrap() as u64;
+			sum += reader.read_bit_vec3_coord().unwrap()[0] as u64;
+			sum += reader.read_uint::<u64>(40).unwrap();
+			sum += reader.read_varint32().unwrap() as u64;
+			sum += reader.read_float().unwrap() as u64;
+		}
+		sum
+	}};
+}
+
+/// Returns the fastest time per run of `run` over a few samples.
+fn measure(mut run: impl FnMut() -> u64) -> Duration {
+	let mut iterations = 1;
+	loop {
+		let start = Instant::now();
+		for _ in 0..iterations {
+			black_box(run());
+		}
+		if start.elapsed() > Duration::from_millis(100) {
+			break;
+		}
+		iterations *= 2;
+	}
+	(0..5)
+		.map(|_| {
+			let start = Instant::now();
+			for _ in 0..iterations {
+				black_box(run());
+			}
+			start.elapsed() / iterations
+		})
+		.min()
+		.unwrap()
+}
+
+fn compare<O: CacheOrder>(order: &str)
+where
+	BitSlice<O, u8>: BitField + LoadBits<O> + StoreBits<O>,
+{
+	let bytes = snapshot::<O>();
+	let vec = measure(|| decode!(BitVecReader::<O>::from_bytes(black_box(bytes.clone()))));
+	let cached = measure(|| decode!(CachedBitReader::<O>::new(black_box(&bytes))));
+	let clone = measure(|| black_box(bytes.clone()).len() as u64);
+	let vec = vec.saturating_sub(clone);
+	println!(
+		"{}: BitVecReader {:?}, CachedBitReader {:?} per {} entities ({:.1}x)",
+		order,
+		vec,
+		cached,
+		ENTITIES,
+		vec.as_secs_f64() / cached.as_secs_f64()
+	);
+}
+
+fn compare_uints<O: CacheOrder>(order: &str, bits: usize)
+where
+	BitSlice<O, u8>: BitField + LoadBits<O> + StoreBits<O>,
+{
+	let samples: Vec<u32> = (0..SAMPLES as u32).map(|i| i % (1 << bits)).collect();
+	let mut writer = BitVecWriter::<O>::new(BitVec::new());
+	writer.write_uints(&samples, bits);
+	let bytes = writer.into_bytes();
+
+	let mut out = vec![0; SAMPLES];
+	let read = measure(|| {
+		let mut reader = BitSliceReader::<O>::from_bytes(black_box(&bytes));
+		for uint in out.iter_mut() {
+			*uint = reader.read_uint(bits).unwrap();
+		}
+		out[SAMPLES - 1] as u64
+	});
+	let read_batch = measure(|| {
+		let mut reader = BitSliceReader::<O>::from_bytes(black_box(&bytes));
+		reader.read_uints_into(&mut out, bits).unwrap();
+		out[SAMPLES - 1] as u64
+	});
+	let write = measure(|| {
+		let mut writer = BitVecWriter::<O>::new(BitVec::with_capacity(SAMPLES * bits));
+		for uint in black_box(&samples) {
+			writer.write_uint(*uint, bits);
+		}
+		writer.bits_written() as u64
+	});
+	let write_batch = measure(|| {
+		let mut writer = BitVecWriter::<O>::new(BitVec::with_capacity(SAMPLES * bits));
+		writer.write_uints(black_box(&samples), bits);
+		writer.bits_written() as u64
+	});
+	println!(
+		"{}: {} {}-bit uints read in {:?}, batched {:?} ({:.1}x), written in {:?}, batched {:?} ({:.1}x)",
+		order,
+		SAMPLES,
+		bits,
+		read,
+		read_batch,
+		read.as_secs_f64() / read_batch.as_secs_f64(),
+		write,
+		write_batch,
+		write.as_secs_f64() / write_batch.as_secs_f64()
+	);
+}
+
+fn main() {
+	compare::<Lsb0>("Lsb0");
+	compare::<Msb0>("Msb0");
+	for bits in [6, 10].iter() {
+		compare_uints::<Lsb0>("Lsb0", *bits);
+		compare_uints::<Msb0>("Msb0", *bits);
+	}
+}
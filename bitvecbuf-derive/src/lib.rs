@@ -0,0 +1,371 @@
+//! Derive macros implementing `bitvecbuf::ToBits` and `bitvecbuf::FromBits`. See `bitvecbuf::encode` for the attributes.
+
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+	parse_macro_input, parse_quote, spanned::Spanned, Attribute, Data, DeriveInput, Error, Expr,
+	Fields, Generics, Ident, Result,
+};
+
+#[proc_macro_derive(BitEncode, attributes(bits, varint, nul_string, skip, cond))]
+pub fn derive_bit_encode(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	bit_encode(input)
+		.unwrap_or_else(Error::into_compile_error)
+		.into()
+}
+
+#[proc_macro_derive(BitDecode, attributes(bits, varint, nul_string, skip, cond))]
+pub fn derive_bit_decode(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	bit_decode(input)
+		.unwrap_or_else(Error::into_compile_error)
+		.into()
+}
+
+/// How a field or an enum discriminant is encoded.
+enum Encoding {
+	Default,
+	Bits(Expr),
+	Varint,
+	NulString,
+	Skip,
+}
+
+struct FieldAttrs {
+	encoding: Encoding,
+	cond: Option<Expr>,
+}
+
+fn parse_attrs(attrs: &[Attribute]) -> Result<FieldAttrs> {
+	let mut encoding = Encoding::Default;
+	let mut cond: Option<Expr> = None;
+	for attr in attrs {
+		let path = attr.path();
+		let new = if path.is_ident("bits") {
+			Encoding::Bits(attr.parse_args()?)
+		} else if path.is_ident("varint") {
+			attr.meta.require_path_only()?;
+			Encoding::Varint
+		} else if path.is_ident("nul_string") {
+			attr.meta.require_path_only()?;
+			Encoding::NulString
+		} else if path.is_ident("skip") {
+			attr.meta.require_path_only()?;
+			Encoding::Skip
+		} else if path.is_ident("cond") {
+			if cond.is_some() {
+				return Err(Error::new(attr.span(), "duplicate `cond` attribute"));
+			}
+			cond = Some(attr.parse_args()?);
+			continue;
+		} else {
+			continue;
+		};
+		if !matches!(encoding, Encoding::Default) {
+			return Err(Error::new(
+				attr.span(),
+				"only one of `bits`, `varint`, `nul_string` and `skip` can be used",
+			));
+		}
+		encoding = new;
+	}
+	if let (Encoding::Skip, Some(cond)) = (&encoding, &cond) {
+		return Err(Error::new(
+			cond.span(),
+			"a skipped field can't have a `cond`",
+		));
+	}
+	Ok(FieldAttrs { encoding, cond })
+}
+
+fn discriminant_encoding(input: &DeriveInput) -> Result<Encoding> {
+	match parse_attrs(&input.attrs)? {
+		FieldAttrs {
+			encoding: encoding @ (Encoding::Bits(_) | Encoding::Varint),
+			cond: None,
+		} => Ok(encoding),
+		_ => Err(Error::new(
+			input.ident.span(),
+			"enums need `#[bits(N)]` or `#[varint]` for their discriminant",
+		)),
+	}
+}
+
+/// The names the fields are bound to, which are the field names or `__field0`, `__field1`... for tuple fields.
+fn bindings(fields: &Fields) -> Vec<Ident> {
+	fields
+		.iter()
+		.enumerate()
+		.map(|(i, field)| {
+			field
+				.ident
+				.clone()
+				.unwrap_or_else(|| format_ident!("__field{}", i))
+		})
+		.collect()
+}
+
+/// A pattern or constructor for `path` with `bindings`.
+fn construct(path: TokenStream, fields: &Fields, bindings: &[Ident]) -> TokenStream {
+	match fields {
+		Fields::Named(_) => quote!(#path { #(#bindings),* }),
+		Fields::Unnamed(_) => quote!(#path ( #(#bindings),* )),
+		Fields::Unit => path,
+	}
+}
+
+fn add_bounds(generics: &Generics, bound: TokenStream) -> Generics {
+	let mut generics = generics.clone();
+	let params: Vec<Ident> = generics
+		.type_params()
+		.map(|param| param.ident.clone())
+		.collect();
+	let where_clause = generics.make_where_clause();
+	for param in params {
+		where_clause.predicates.push(parse_quote!(#param: #bound));
+	}
+	generics
+}
+
+fn encode_value(encoding: &Encoding, value: &TokenStream) -> TokenStream {
+	match encoding {
+		Encoding::Default => quote!(::bitvecbuf::ToBits::to_bits(#value, __writer)),
+		Encoding::Bits(bits) => {
+			quote!(::bitvecbuf::encode::__private::Bits::encode_bits(#value, __writer, #bits))
+		}
+		Encoding::Varint => {
+			quote!(::bitvecbuf::encode::__private::Varint::encode_varint(#value, __writer))
+		}
+		Encoding::NulString => quote!(__writer.write_string_nul(#value)),
+		Encoding::Skip => quote!(),
+	}
+}
+
+fn decode_value(encoding: &Encoding) -> TokenStream {
+	match encoding {
+		Encoding::Default => quote!(::bitvecbuf::FromBits::from_bits(__reader)?),
+		Encoding::Bits(bits) => {
+			quote!(::bitvecbuf::encode::__private::Bits::decode_bits(__reader, #bits)?)
+		}
+		Encoding::Varint => quote!(::bitvecbuf::encode::__private::Varint::decode_varint(
+			__reader
+		)?),
+		Encoding::NulString => quote!(__reader.read_nul_string()?),
+		Encoding::Skip => quote!(::core::default::Default::default()),
+	}
+}
+
+/// Writes the fields, which are bound by reference to `bindings`.
+fn encode_fields(fields: &Fields, bindings: &[Ident]) -> Result<TokenStream> {
+	let mut encode = TokenStream::new();
+	for (field, binding) in fields.iter().zip(bindings) {
+		let FieldAttrs { encoding, cond } = parse_attrs(&field.attrs)?;
+		encode.extend(match cond {
+			None => {
+				let value = encode_value(&encoding, &quote!(#binding));
+				quote!(#value;)
+			}
+			Some(cond) => {
+				let value = encode_value(&encoding, &quote!(__value));
+				let message = format!("`{}` must be `Some` when its condition is true", binding);
+				quote! {
+					if #cond {
+						match #binding {
+							::core::option::Option::Some(__value) => { #value; }
+							::core::option::Option::None => panic!(#message),
+						}
+					}
+				}
+			}
+		});
+	}
+	Ok(encode)
+}
+
+/// Reads the fields into `bindings`.
+fn decode_fields(fields: &Fields, bindings: &[Ident]) -> Result<TokenStream> {
+	let mut decode = TokenStream::new();
+	for (i, (field, binding)) in fields.iter().zip(bindings).enumerate() {
+		let FieldAttrs { encoding, cond } = parse_attrs(&field.attrs)?;
+		let ty = &field.ty;
+		let value = decode_value(&encoding);
+		let value = match cond {
+			None => value,
+			Some(cond) => {
+				let previous = &bindings[..i];
+				quote! {
+					if { #(#[allow(unused_variables)] let #previous = &#previous;)* #cond } {
+						::core::option::Option::Some(#value)
+					} else {
+						::core::option::Option::None
+					}
+				}
+			}
+		};
+		decode.extend(quote!(let #binding: #ty = #value;));
+	}
+	Ok(decode)
+}
+
+fn bit_encode(input: DeriveInput) -> Result<TokenStream> {
+	let body = match &input.data {
+		Data::Struct(data) => {
+			let bindings = bindings(&data.fields);
+			let pattern = construct(quote!(Self), &data.fields, &bindings);
+			let encode = encode_fields(&data.fields, &bindings)?;
+			quote! {
+				let #pattern = self;
+				#encode
+			}
+		}
+		Data::Enum(data) => {
+			let discriminant = discriminant_encoding(&input)?;
+			let mut arms = TokenStream::new();
+			for (variant, value) in data
+				.variants
+				.iter()
+				.zip(discriminants(data.variants.iter()))
+			{
+				let ident = &variant.ident;
+				let bindings = bindings(&variant.fields);
+				let pattern = construct(quote!(Self::#ident), &variant.fields, &bindings);
+				let value = discriminant_value(&discriminant, &value);
+				let write_discriminant = encode_value(&discriminant, &quote!(&#value));
+				let encode = encode_fields(&variant.fields, &bindings)?;
+				arms.extend(quote! {
+					#pattern => {
+						#write_discriminant;
+						#encode
+					}
+				});
+			}
+			quote! {
+				match self {
+					#arms
+				}
+			}
+		}
+		Data::Union(data) => {
+			return Err(Error::new(
+				data.union_token.span,
+				"unions can't derive `BitEncode`",
+			))
+		}
+	};
+
+	let ident = &input.ident;
+	let generics = add_bounds(&input.generics, quote!(::bitvecbuf::ToBits));
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+	Ok(quote! {
+		impl #impl_generics ::bitvecbuf::ToBits for #ident #ty_generics #where_clause {
+			#[allow(unused_variables)]
+			fn to_bits<__W: ::bitvecbuf::BitWrite>(&self, __writer: &mut __W) {
+				#body
+			}
+		}
+	})
+}
+
+fn bit_decode(input: DeriveInput) -> Result<TokenStream> {
+	let body = match &input.data {
+		Data::Struct(data) => {
+			let bindings = bindings(&data.fields);
+			let decode = decode_fields(&data.fields, &bindings)?;
+			let construct = construct(quote!(Self), &data.fields, &bindings);
+			quote! {
+				#decode
+				Ok(#construct)
+			}
+		}
+		Data::Enum(data) => {
+			let discriminant = discriminant_encoding(&input)?;
+			let read_discriminant = decode_value(&discriminant);
+			let mut arms = TokenStream::new();
+			for (variant, value) in data
+				.variants
+				.iter()
+				.zip(discriminants(data.variants.iter()))
+			{
+				let ident = &variant.ident;
+				let bindings = bindings(&variant.fields);
+				let decode = decode_fields(&variant.fields, &bindings)?;
+				let construct = construct(quote!(Self::#ident), &variant.fields, &bindings);
+				let value = discriminant_value(&discriminant, &value);
+				arms.extend(quote! {
+					if __discriminant == #value {
+						#decode
+						return Ok(#construct);
+					}
+				});
+			}
+			quote! {
+				let __offset = ::bitvecbuf::BitRead::bits_read(__reader);
+				let __discriminant: u64 = #read_discriminant;
+				#arms
+				Err(::bitvecbuf::ReadError::InvalidValue {
+					offset: __offset,
+					value: __discriminant,
+				})
+			}
+		}
+		Data::Union(data) => {
+			return Err(Error::new(
+				data.union_token.span,
+				"unions can't derive `BitDecode`",
+			))
+		}
+	};
+
+	let ident = &input.ident;
+	let generics = add_bounds(&input.generics, quote!(::bitvecbuf::FromBits));
+	let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+	Ok(quote! {
+		impl #impl_generics ::bitvecbuf::FromBits for #ident #ty_generics #where_clause {
+			fn from_bits<__R: ::bitvecbuf::BitRead>(
+				__reader: &mut __R,
+			) -> ::core::result::Result<Self, ::bitvecbuf::ReadError> {
+				::bitvecbuf::BitRead::atomically(
+					__reader,
+					|__reader| -> ::core::result::Result<Self, ::bitvecbuf::ReadError> { #body },
+				)
+			}
+		}
+	})
+}
+
+/// The `u64` a discriminant is written as. It goes through `i128` so that an unsuffixed negative literal isn't
+/// inferred as `u64`, and with `#[bits(N)]` it's masked to `N` bits so that negative discriminants read back as
+/// themselves.
+fn discriminant_value(encoding: &Encoding, value: &TokenStream) -> TokenStream {
+	let value = quote!((#value) as i128 as u64);
+	match encoding {
+		Encoding::Bits(bits) => {
+			quote!(::bitvecbuf::encode::__private::mask_discriminant(#value, #bits))
+		}
+		_ => value,
+	}
+}
+
+/// The discriminant of each variant: its explicit discriminant, or one more than the previous variant's.
+fn discriminants<'a>(variants: impl Iterator<Item = &'a syn::Variant>) -> Vec<TokenStream> {
+	let mut base: Option<&Expr> = None;
+	let mut offset = 0_u64;
+	variants
+		.map(|variant| {
+			if let Some((_, discriminant)) = &variant.discriminant {
+				base = Some(discriminant);
+				offset = 0;
+			}
+			// Unsuffixed, so that the sum takes the type of the explicit discriminant, which may be negative
+			let literal = Literal::u64_unsuffixed(offset);
+			let value = match base {
+				Some(base) if offset == 0 => quote!(#base),
+				Some(base) => quote!((#base) + #literal),
+				None => quote!(#literal),
+			};
+			offset += 1;
+			value
+		})
+		.collect()
+}
@@ -0,0 +1,561 @@
+use std::{convert::TryInto, marker::PhantomData};
+
+use bitvec::{
+	macros::internal::funty::{IsNumber, IsSigned, IsUnsigned},
+	mem::BitMemory,
+	order::{BitOrder, Lsb0, Msb0},
+};
+
+use crate::{
+	coord::{
+		COORD_FRACTIONAL_BITS, COORD_FRACTIONAL_BITS_MP_LOWPRECISION, COORD_INTEGER_BITS,
+		COORD_INTEGER_BITS_MP, COORD_RESOLUTION, COORD_RESOLUTION_LOWPRECISION,
+		NORMAL_FRACTIONAL_BITS, NORMAL_RESOLUTION,
+	},
+	read::{
+		byte_bits, forward_bit_read, forward_bit_read_unchecked, shared_reads, zigzag_decode32,
+		zigzag_decode64, FromBitMemory,
+	},
+	BitCount, BitRead, ReadError, FIELD_INDEX_END, MAX_VARINT32_BYTES, MAX_VARINT64_BYTES,
+};
+
+/// The most bits `CachedBitReader::take` can take at once, which a refill always makes available if they are left.
+const MAX_TAKE_BITS: u32 = 56;
+
+/// Reads bits from borrowed bytes through a 64-bit cache of the bits ahead of the cursor.
+///
+/// This reads the same bits the same way as a [`BitVecReader`](crate::BitVecReader) over the same bytes, with the same
+/// errors and the same all-or-nothing behaviour, but most reads are a shift and a mask of the cache rather than a
+/// [`BitSlice`](bitvec::slice::BitSlice) load. The cache is refilled 8 bytes at a time with a single unaligned load.
+#[derive(Debug)]
+pub struct CachedBitReader<'a, O: CacheOrder> {
+	bytes: &'a [u8],
+	/// The index of the next byte to load into the cache.
+	pos: usize,
+	/// The bits ahead of the cursor, laid out by `O`.
+	cache: u64,
+	/// The number of bits in `cache`.
+	cached: u32,
+	order: PhantomData<O>,
+}
+impl<'a, O: CacheOrder> CachedBitReader<'a, O> {
+	#[inline]
+	pub fn new(bytes: &'a [u8]) -> CachedBitReader<'a, O> {
+		CachedBitReader {
+			bytes,
+			pos: 0,
+			cache: 0,
+			cached: 0,
+			order: PhantomData,
+		}
+	}
+
+	#[inline]
+	pub fn into_bytes(self) -> &'a [u8] {
+		self.bytes
+	}
+
+	#[inline]
+	pub fn cursor(&self) -> usize {
+		self.pos * 8 - self.cached as usize
+	}
+
+	#[inline]
+	pub fn bits_len(&self) -> usize {
+		self.bytes.len() * 8
+	}
+
+	#[inline]
+	pub fn bytes_len(&self) -> usize {
+		self.bytes.len()
+	}
+
+	#[inline]
+	pub fn bits_left(&self) -> usize {
+		self.bits_len() - self.cursor()
+	}
+
+	#[inline]
+	pub fn bytes_left(&self) -> usize {
+		(self.bits_left() + 7) / 8
+	}
+
+	#[inline]
+	pub fn bits_read(&self) -> usize {
+		self.cursor()
+	}
+
+	#[inline]
+	pub fn bytes_read(&self) -> usize {
+		(self.bits_read() + 7) / 8
+	}
+
+	/// Moves the cursor to `bit`. Will panic if it is past the end.
+	pub fn seek(&mut self, bit: usize) {
+		assert!(bit <= self.bits_len(), "seeked past the end");
+		self.pos = bit / 8;
+		self.cache = 0;
+		self.cached = 0;
+		let bits_left = bit % 8;
+		if bits_left > 0 {
+			self.take(bits_left as u32);
+		}
+	}
+
+	/// Moves the cursor forward `n` bits, stopping at the end.
+	#[inline]
+	pub fn advance(&mut self, n: usize) {
+		if n <= (self.cached as usize).min(MAX_TAKE_BITS as usize) {
+			self.take(n as u32);
+		} else {
+			self.seek((self.cursor() + n).min(self.bits_len()));
+		}
+	}
+
+	#[inline]
+	pub fn rewind(&mut self, n: usize) {
+		self.seek(self.cursor() - n);
+	}
+
+	/// Loads as many bytes into the cache as fit, up to the end of the bytes.
+	#[inline]
+	fn refill(&mut self) {
+		if let Some(word) = self.bytes.get(self.pos..self.pos + 8) {
+			// Load a whole word, but only count the bytes that fit; the rest are loaded again next time.
+			self.cache |= O::place(O::load_word(word.try_into().unwrap()), self.cached);
+			self.pos += (63 - self.cached as usize) / 8;
+			self.cached |= 56;
+		} else {
+			while self.cached <= 56 && self.pos < self.bytes.len() {
+				let word = O::load_word([self.bytes[self.pos], 0, 0, 0, 0, 0, 0, 0]);
+				self.cache |= O::place(word, self.cached);
+				self.pos += 1;
+				self.cached += 8;
+			}
+		}
+	}
+
+	/// Takes the next `bits` bits, for `bits` in `0..=MAX_TAKE_BITS`. The bits must be there.
+	#[inline]
+	fn take(&mut self, bits: u32) -> u64 {
+		if bits == 0 {
+			return 0;
+		}
+		if self.cached < bits {
+			self.refill();
+		}
+		let value = O::peek(self.cache, bits);
+		self.cache = O::consume(self.cache, bits);
+		self.cached -= bits;
+		value
+	}
+
+	/// Takes the next `bits` bits, for `bits` in `1..=128`. The bits must be there.
+	#[inline]
+	fn take_wide(&mut self, bits: u32) -> u128 {
+		if bits <= MAX_TAKE_BITS {
+			return self.take(bits) as u128;
+		}
+		let first_bits = bits / 2;
+		let second_bits = bits - first_bits;
+		let first = self.take_wide(first_bits);
+		let second = self.take_wide(second_bits);
+		O::join(first, first_bits, second, second_bits)
+	}
+
+	#[inline]
+	fn check_range(&self, bits: usize) -> Result<(), ReadError> {
+		if self.bits_left() < bits {
+			Err(ReadError::OutOfBounds {
+				offset: self.cursor(),
+				requested: bits,
+				available: self.bits_left(),
+			})
+		} else {
+			Ok(())
+		}
+	}
+
+	#[inline]
+	fn check_width(&self, bits: usize, max: usize) -> Result<(), ReadError> {
+		if bits == 0 || bits > max {
+			Err(ReadError::OutOfRange {
+				offset: self.cursor(),
+				bits,
+				max,
+			})
+		} else {
+			Ok(())
+		}
+	}
+
+	#[inline]
+	pub fn read_bit(&mut self) -> Result<bool, ReadError> {
+		self.check_range(1)?;
+		Ok(self.take(1) != 0)
+	}
+
+	#[inline]
+	pub fn read_byte(&mut self) -> Result<u8, ReadError> {
+		self.check_range(8)?;
+		Ok(self.take(8) as u8)
+	}
+
+	pub fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>, ReadError> {
+		self.check_range(byte_bits(len))?;
+		if self.cached % 8 == 0 {
+			let start = self.cursor() / 8;
+			self.seek((start + len) * 8);
+			Ok(self.bytes[start..start + len].to_vec())
+		} else {
+			Ok((0..len).map(|_| self.take(8) as u8).collect())
+		}
+	}
+
+	#[inline]
+	pub fn read_uint<N>(&mut self, bits: usize) -> Result<N, ReadError>
+	where
+		N: BitMemory + IsNumber + IsUnsigned,
+	{
+		self.check_width(bits, N::BITS as usize)?;
+		self.check_range(bits)?;
+		Ok(self.take_uint(bits as u32))
+	}
+
+	/// Reads `out.len()` consecutive `bits`-bit uints into `out`, for `bits` in `1..=32`. If there aren't enough bits
+	/// left, `out` is left untouched.
+	pub fn read_uints_into(&mut self, out: &mut [u32], bits: usize) -> Result<(), ReadError> {
+		self.check_width(bits, 32)?;
+		self.check_range(out.len() * bits)?;
+		self.take_uints(out, bits);
+		Ok(())
+	}
+
+	/// Takes the next `out.len()` `bits`-bit uints, for `bits` in `1..=32`. The bits must be there.
+	#[inline]
+	pub(crate) fn take_uints(&mut self, out: &mut [u32], bits: usize) {
+		specialize_width!(bits as u32, |bits| {
+			for uint in out.iter_mut() {
+				*uint = self.take(bits) as u32;
+			}
+		});
+	}
+
+	#[inline]
+	pub fn read_int<N>(&mut self, bits: usize) -> Result<N, ReadError>
+	where
+		N: FromBitMemory + IsNumber + IsSigned,
+	{
+		self.check_width(bits, N::BIT_COUNT)?;
+		self.check_range(bits)?;
+		Ok(N::from_bitmemory(self.take_uint(bits as u32), bits))
+	}
+
+	/// Takes the next `bits` bits as an `N`, which they must fit in. The bits must be there.
+	#[inline]
+	fn take_uint<N: IsUnsigned>(&mut self, bits: u32) -> N {
+		if bits <= MAX_TAKE_BITS {
+			N::try_from(self.take(bits)).unwrap_or_else(|_| unreachable!())
+		} else {
+			N::try_from(self.take_wide(bits)).unwrap_or_else(|_| unreachable!())
+		}
+	}
+
+	#[inline]
+	pub fn read_float(&mut self) -> Result<f32, ReadError> {
+		self.check_range(f32::BIT_COUNT)?;
+		Ok(f32::from_bits(self.take(32) as u32))
+	}
+
+	#[inline]
+	pub fn read_double(&mut self) -> Result<f64, ReadError> {
+		self.check_range(64)?;
+		Ok(f64::from_bits(self.take_wide(64) as u64))
+	}
+
+	fn read_varint(&mut self, max_bytes: usize) -> Result<u64, ReadError> {
+		let start = *self;
+		let mut varint = 0;
+		for i in 0..max_bytes {
+			if self.bits_left() < 8 {
+				*self = start;
+				return Err(ReadError::OutOfBounds {
+					offset: start.cursor(),
+					requested: (i + 1) * 8,
+					available: start.bits_left(),
+				});
+			}
+			let byte = self.take(8);
+			varint |= (byte & 0x7F) << (7 * i);
+			if byte & 0x80 == 0 {
+				return Ok(varint);
+			}
+		}
+		*self = start;
+		Err(ReadError::LengthLimitExceeded {
+			offset: start.cursor(),
+			limit: max_bytes,
+		})
+	}
+
+	shared_reads!(pub, Self::read_varint);
+
+	#[inline]
+	pub fn read_string(&mut self, bytes: usize) -> Result<String, ReadError> {
+		let start = *self;
+		let bytes = self.read_bytes(bytes)?;
+		String::from_utf8(bytes).map_err(|error| {
+			*self = start;
+			ReadError::InvalidUtf8 {
+				offset: start.cursor(),
+				error: error.utf8_error(),
+			}
+		})
+	}
+
+	pub fn read_nul_string(&mut self) -> Result<String, ReadError> {
+		let start = *self;
+		let string = self.load_nul_string()?;
+		String::from_utf8(string).map_err(|error| {
+			*self = start;
+			ReadError::InvalidUtf8 {
+				offset: start.cursor(),
+				error: error.utf8_error(),
+			}
+		})
+	}
+
+	/// # Safety
+	///
+	/// The bytes read must be valid UTF-8.
+	pub unsafe fn read_nul_string_unchecked(&mut self) -> Result<String, ReadError> {
+		self.load_nul_string()
+			.map(|string| String::from_utf8_unchecked(string))
+	}
+
+	pub fn read_nul_string_lossy(&mut self) -> Result<String, ReadError> {
+		self.load_nul_string()
+			.map(|string| String::from_utf8_lossy(&string).into_owned())
+	}
+
+	/// Reads the bytes up to the next nul terminator, and the terminator.
+	fn load_nul_string(&mut self) -> Result<Vec<u8>, ReadError> {
+		if self.cached % 8 == 0 {
+			let start = self.cursor() / 8;
+			return match self.bytes[start..].iter().position(|&byte| byte == 0) {
+				Some(len) => {
+					self.seek((start + len + 1) * 8);
+					Ok(self.bytes[start..start + len].to_vec())
+				}
+				None => Err(ReadError::OutOfBounds {
+					offset: self.cursor(),
+					requested: (self.bytes.len() - start + 1) * 8,
+					available: self.bits_left(),
+				}),
+			};
+		}
+		let start = *self;
+		let mut string = Vec::new();
+		while self.bits_left() >= 8 {
+			match self.take(8) as u8 {
+				0 => return Ok(string),
+				byte => string.push(byte),
+			}
+		}
+		*self = start;
+		Err(ReadError::OutOfBounds {
+			offset: start.cursor(),
+			requested: (string.len() + 1) * 8,
+			available: start.bits_left(),
+		})
+	}
+
+	/// Runs `read` and moves the cursor back to where it was, whether it succeeded or not.
+	#[inline]
+	pub fn peek_with<T>(
+		&mut self,
+		read: impl FnOnce(&mut Self) -> Result<T, ReadError>,
+	) -> Result<T, ReadError> {
+		let start = *self;
+		let result = read(self);
+		*self = start;
+		result
+	}
+
+	#[inline]
+	pub fn peek_bit(&mut self) -> Result<bool, ReadError> {
+		self.peek_with(Self::read_bit)
+	}
+
+	#[inline]
+	pub fn peek_bytes(&mut self, len: usize) -> Result<Vec<u8>, ReadError> {
+		self.peek_with(|reader| reader.read_bytes(len))
+	}
+
+	#[inline]
+	pub fn peek_uint<N>(&mut self, bits: usize) -> Result<N, ReadError>
+	where
+		N: BitMemory + IsNumber + IsUnsigned,
+	{
+		self.peek_with(|reader| reader.read_uint(bits))
+	}
+
+	#[inline]
+	pub fn peek_int<N>(&mut self, bits: usize) -> Result<N, ReadError>
+	where
+		N: FromBitMemory + IsNumber + IsSigned,
+	{
+		self.peek_with(|reader| reader.read_int(bits))
+	}
+
+	/// Runs a read made up of several smaller reads, rewinding the cursor if any of them fail.
+	#[inline]
+	fn atomically<T>(
+		&mut self,
+		read: impl FnOnce(&mut Self) -> Result<T, ReadError>,
+	) -> Result<T, ReadError> {
+		let start = *self;
+		let result = read(self);
+		if result.is_err() {
+			*self = start;
+		}
+		result
+	}
+}
+impl<O: CacheOrder> Clone for CachedBitReader<'_, O> {
+	#[inline]
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+impl<O: CacheOrder> Copy for CachedBitReader<'_, O> {}
+impl<'a, O: CacheOrder> From<&'a [u8]> for CachedBitReader<'a, O> {
+	#[inline]
+	fn from(bytes: &'a [u8]) -> Self {
+		CachedBitReader::new(bytes)
+	}
+}
+impl<O: CacheOrder> BitRead for CachedBitReader<'_, O> {
+	#[inline]
+	fn bits_left(&self) -> Option<usize> {
+		Some(Self::bits_left(self))
+	}
+
+	forward_bit_read!();
+	forward_bit_read_unchecked!();
+}
+
+/// Calls `$f` with `$bits` as a constant for the widths that are worth specializing, so the shifts and masks it
+/// inlines into fold away.
+macro_rules! specialize_width {
+	( $bits:expr, $f:expr ) => {
+		match $bits {
+			1 => $f(1),
+			2 => $f(2),
+			3 => $f(3),
+			4 => $f(4),
+			5 => $f(5),
+			6 => $f(6),
+			7 => $f(7),
+			8 => $f(8),
+			9 => $f(9),
+			10 => $f(10),
+			11 => $f(11),
+			12 => $f(12),
+			13 => $f(13),
+			14 => $f(14),
+			15 => $f(15),
+			16 => $f(16),
+			bits => $f(bits),
+		}
+	};
+}
+pub(crate) use specialize_width;
+
+/// How a [`CachedBitReader`] lays out its cache for a bit order.
+///
+/// [`Lsb0`] reads the low bits of each byte first, so the cache holds the next bit in its lowest bit and is consumed
+/// by shifting right. [`Msb0`] reads the high bits first, so the cache holds the next bit in its highest bit and is
+/// consumed by shifting left.
+pub trait CacheOrder: BitOrder {
+	/// Loads 8 bytes as a word with the first bit to be read where the cache keeps it.
+	fn load_word(bytes: [u8; 8]) -> u64;
+
+	/// Moves a loaded word past the `cached` bits already in the cache.
+	fn place(word: u64, cached: u32) -> u64;
+
+	/// Returns the next `bits` bits in the cache, for `bits` in `1..=56`.
+	fn peek(cache: u64, bits: u32) -> u64;
+
+	/// Drops the next `bits` bits from the cache, for `bits` in `1..=56`.
+	fn consume(cache: u64, bits: u32) -> u64;
+
+	/// Lays out the low `bits` bits of `uint` as the next bits of an empty cache, for `bits` in `1..=56`. This is the
+	/// inverse of [`peek`](CacheOrder::peek).
+	fn put(uint: u64, bits: u32) -> u64;
+
+	/// Joins two values read one after the other into one, as if they had been read at once.
+	fn join(first: u128, first_bits: u32, second: u128, second_bits: u32) -> u128;
+}
+impl CacheOrder for Lsb0 {
+	#[inline]
+	fn load_word(bytes: [u8; 8]) -> u64 {
+		u64::from_le_bytes(bytes)
+	}
+
+	#[inline]
+	fn place(word: u64, cached: u32) -> u64 {
+		word << cached
+	}
+
+	#[inline]
+	fn peek(cache: u64, bits: u32) -> u64 {
+		cache & (u64::MAX >> (64 - bits))
+	}
+
+	#[inline]
+	fn consume(cache: u64, bits: u32) -> u64 {
+		cache >> bits
+	}
+
+	#[inline]
+	fn put(uint: u64, bits: u32) -> u64 {
+		uint & (u64::MAX >> (64 - bits))
+	}
+
+	#[inline]
+	fn join(first: u128, first_bits: u32, second: u128, _second_bits: u32) -> u128 {
+		first | second << first_bits
+	}
+}
+impl CacheOrder for Msb0 {
+	#[inline]
+	fn load_word(bytes: [u8; 8]) -> u64 {
+		u64::from_be_bytes(bytes)
+	}
+
+	#[inline]
+	fn place(word: u64, cached: u32) -> u64 {
+		word >> cached
+	}
+
+	#[inline]
+	fn peek(cache: u64, bits: u32) -> u64 {
+		cache >> (64 - bits)
+	}
+
+	#[inline]
+	fn consume(cache: u64, bits: u32) -> u64 {
+		cache << bits
+	}
+
+	#[inline]
+	fn put(uint: u64, bits: u32) -> u64 {
+		uint << (64 - bits)
+	}
+
+	#[inline]
+	fn join(first: u128, _first_bits: u32, second: u128, second_bits: u32) -> u128 {
+		first << second_bits | second
+	}
+}
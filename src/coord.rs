@@ -0,0 +1,18 @@
+//! Constants for Source engine's coordinate, normal and angle encodings, as defined in `coordsize.h`.
+//!
+//! The resolutions are `f64` because Source defines them as double literals, which makes some of its float
+//! arithmetic happen in double precision. The encoders rely on this to be bit-exact.
+
+pub(crate) const COORD_INTEGER_BITS: usize = 14;
+pub(crate) const COORD_FRACTIONAL_BITS: usize = 5;
+pub(crate) const COORD_DENOMINATOR: u32 = 1 << COORD_FRACTIONAL_BITS;
+pub(crate) const COORD_RESOLUTION: f64 = 1.0 / COORD_DENOMINATOR as f64;
+
+pub(crate) const COORD_INTEGER_BITS_MP: usize = 11;
+pub(crate) const COORD_FRACTIONAL_BITS_MP_LOWPRECISION: usize = 3;
+pub(crate) const COORD_DENOMINATOR_LOWPRECISION: u32 = 1 << COORD_FRACTIONAL_BITS_MP_LOWPRECISION;
+pub(crate) const COORD_RESOLUTION_LOWPRECISION: f64 = 1.0 / COORD_DENOMINATOR_LOWPRECISION as f64;
+
+pub(crate) const NORMAL_FRACTIONAL_BITS: usize = 11;
+pub(crate) const NORMAL_DENOMINATOR: u32 = (1 << NORMAL_FRACTIONAL_BITS) - 1;
+pub(crate) const NORMAL_RESOLUTION: f64 = 1.0 / NORMAL_DENOMINATOR as f64;
@@ -0,0 +1,373 @@
+//! Types that know how to write and read themselves.
+//!
+//! [`ToBits`] and [`FromBits`] are implemented for the primitives, `bool`, `char`, arrays, tuples, `Option`, `Vec`
+//! and `String`, so any of them can be written with [`BitWrite::write`] and read with [`BitRead::read`]:
+//!
+//! | Type                 | Encoding                                                                |
+//! |----------------------|-------------------------------------------------------------------------|
+//! | Integers             | All of their bits, with `usize` and `isize` as 64-bit integers          |
+//! | `bool`               | One bit                                                                 |
+//! | `char`               | Its scalar value as a `u32`                                             |
+//! | `f32`, `f64`         | [`write_float`](BitWrite::write_float)/[`write_double`](BitWrite::write_double) |
+//! | `[T; N]`, tuples     | Each element in order                                                   |
+//! | `Option<T>`          | A bit for whether it is `Some`, followed by the value if it is          |
+//! | `Vec<T>`, `[T]`      | The length as a [`varint64`](BitWrite::write_varint64), followed by each element |
+//! | `String`, `str`      | The length in bytes as a [`varint64`](BitWrite::write_varint64), followed by the bytes |
+//!
+//! With the `derive` feature, `#[derive(BitEncode, BitDecode)]` implements [`ToBits`] and [`FromBits`] for structs
+//! and enums. Each field is encoded in order with its own [`ToBits`] implementation unless it has one of these
+//! attributes:
+//!
+//! | Attribute         | Encoding                                                                   |
+//! |-------------------|----------------------------------------------------------------------------|
+//! | `#[bits(N)]`      | An integer written with [`write_uint`](BitWrite::write_uint)/[`write_int`](BitWrite::write_int) in `N` bits |
+//! | `#[varint]`       | A 32 or 64-bit integer written as a varint, zigzag-encoded if signed      |
+//! | `#[nul_string]`   | A `String` written with [`write_string_nul`](BitWrite::write_string_nul) |
+//! | `#[skip]`         | Not written, and set to [`Default::default`] when read                    |
+//! | `#[cond(expr)]`   | An `Option` that is only written if `expr` is true                        |
+//!
+//! The expression in `#[cond(expr)]` can refer to the fields before it by name, as references. When writing, the
+//! field must be `Some` if the condition is true, and is not written if it is false.
+//!
+//! Enums need `#[bits(N)]` or `#[varint]` on the enum itself for the discriminant, which is either the variant's
+//! explicit discriminant or one more than the previous variant's. The variant's fields follow it.
+//!
+//! ```ignore
+//! #[derive(BitEncode, BitDecode)]
+//! struct Header {
+//!     #[bits(12)]
+//!     id: u16,
+//!     has_name: bool,
+//!     #[cond(*has_name)]
+//!     #[nul_string]
+//!     name: Option<String>,
+//! }
+//! ```
+
+use std::{
+	convert::{TryFrom, TryInto},
+	mem,
+};
+
+use crate::{BitRead, BitWrite, ReadError};
+
+/// A value that can be written with [`BitWrite::write`].
+pub trait ToBits {
+	fn to_bits<W: BitWrite>(&self, writer: &mut W);
+}
+
+/// A value that can be read with [`BitRead::read`].
+pub trait FromBits: Sized {
+	/// Reads a `Self`. If this fails, the cursor is left where it was.
+	fn from_bits<R: BitRead>(reader: &mut R) -> Result<Self, ReadError>;
+}
+
+impl ToBits for bool {
+	#[inline]
+	fn to_bits<W: BitWrite>(&self, writer: &mut W) {
+		writer.write_bit(*self);
+	}
+}
+impl FromBits for bool {
+	#[inline]
+	fn from_bits<R: BitRead>(reader: &mut R) -> Result<Self, ReadError> {
+		reader.read_bit()
+	}
+}
+
+impl ToBits for f32 {
+	#[inline]
+	fn to_bits<W: BitWrite>(&self, writer: &mut W) {
+		writer.write_float(*self);
+	}
+}
+impl FromBits for f32 {
+	#[inline]
+	fn from_bits<R: BitRead>(reader: &mut R) -> Result<Self, ReadError> {
+		reader.read_float()
+	}
+}
+
+impl ToBits for f64 {
+	#[inline]
+	fn to_bits<W: BitWrite>(&self, writer: &mut W) {
+		writer.write_double(*self);
+	}
+}
+impl FromBits for f64 {
+	#[inline]
+	fn from_bits<R: BitRead>(reader: &mut R) -> Result<Self, ReadError> {
+		reader.read_double()
+	}
+}
+
+macro_rules! impl_int {
+	( $write:ident, $read:ident, $($ty:ty),* ) => {$(
+		impl ToBits for $ty {
+			#[inline]
+			fn to_bits<W: BitWrite>(&self, writer: &mut W)
+			{
+				writer.$write(*self, <$ty>::BITS as usize);
+			}
+		}
+		impl FromBits for $ty {
+			#[inline]
+			fn from_bits<R: BitRead>(reader: &mut R) -> Result<Self, ReadError>
+			{
+				reader.$read(<$ty>::BITS as usize)
+			}
+		}
+		impl __private::Bits for $ty {
+			#[inline]
+			fn encode_bits<W: BitWrite>(&self, writer: &mut W, bits: usize)
+			{
+				writer.$write(*self, bits);
+			}
+
+			#[inline]
+			fn decode_bits<R: BitRead>(reader: &mut R, bits: usize) -> Result<Self, ReadError>
+			{
+				reader.$read(bits)
+			}
+		}
+	)*};
+}
+impl_int!(write_uint, read_uint, u8, u16, u32, u64, u128);
+impl_int!(write_int, read_int, i8, i16, i32, i64, i128);
+
+macro_rules! impl_varint {
+	( $ty:ty, $write:ident, $read:ident ) => {
+		impl __private::Varint for $ty {
+			#[inline]
+			fn encode_varint<W: BitWrite>(&self, writer: &mut W) {
+				writer.$write(*self);
+			}
+
+			#[inline]
+			fn decode_varint<R: BitRead>(reader: &mut R) -> Result<Self, ReadError> {
+				reader.$read()
+			}
+		}
+	};
+}
+impl_varint!(u32, write_varint32, read_varint32);
+impl_varint!(u64, write_varint64, read_varint64);
+impl_varint!(i32, write_signed_varint32, read_signed_varint32);
+impl_varint!(i64, write_signed_varint64, read_signed_varint64);
+
+macro_rules! impl_size {
+	( $($ty:ty => $inner:ty),* ) => {$(
+		impl ToBits for $ty {
+			#[inline]
+			fn to_bits<W: BitWrite>(&self, writer: &mut W)
+			{
+				(*self as $inner).to_bits(writer);
+			}
+		}
+		impl FromBits for $ty {
+			#[inline]
+			fn from_bits<R: BitRead>(reader: &mut R) -> Result<Self, ReadError>
+			{
+				reader.atomically(|reader| {
+					let offset = reader.bits_read();
+					let value = <$inner>::from_bits(reader)?;
+					value.try_into().map_err(|_| ReadError::InvalidValue {
+						offset,
+						value: value as u64,
+					})
+				})
+			}
+		}
+	)*};
+}
+impl_size!(usize => u64, isize => i64);
+
+impl ToBits for char {
+	#[inline]
+	fn to_bits<W: BitWrite>(&self, writer: &mut W) {
+		(*self as u32).to_bits(writer);
+	}
+}
+impl FromBits for char {
+	#[inline]
+	fn from_bits<R: BitRead>(reader: &mut R) -> Result<Self, ReadError> {
+		reader.atomically(|reader| {
+			let offset = reader.bits_read();
+			let value = u32::from_bits(reader)?;
+			char::try_from(value).map_err(|_| ReadError::InvalidValue {
+				offset,
+				value: value as u64,
+			})
+		})
+	}
+}
+
+impl<T: ToBits, const N: usize> ToBits for [T; N] {
+	#[inline]
+	fn to_bits<W: BitWrite>(&self, writer: &mut W) {
+		for value in self {
+			value.to_bits(writer);
+		}
+	}
+}
+impl<T: FromBits, const N: usize> FromBits for [T; N] {
+	fn from_bits<R: BitRead>(reader: &mut R) -> Result<Self, ReadError> {
+		reader.atomically(|reader| {
+			let mut values = Vec::with_capacity(N);
+			for _ in 0..N {
+				values.push(T::from_bits(reader)?);
+			}
+			Ok(values.try_into().unwrap_or_else(|_| unreachable!()))
+		})
+	}
+}
+
+macro_rules! impl_tuple {
+	( $( ($($ty:ident $index:tt),*) )* ) => {$(
+		impl<$($ty: ToBits),*> ToBits for ($($ty,)*) {
+			#[inline]
+			#[allow(unused_variables)]
+			fn to_bits<W: BitWrite>(&self, writer: &mut W)
+			{
+				$(self.$index.to_bits(writer);)*
+			}
+		}
+		impl<$($ty: FromBits),*> FromBits for ($($ty,)*) {
+			#[inline]
+			#[allow(unused_variables)]
+			fn from_bits<R: BitRead>(reader: &mut R) -> Result<Self, ReadError>
+			{
+				reader.atomically(|reader| Ok(($($ty::from_bits(reader)?,)*)))
+			}
+		}
+	)*};
+}
+impl_tuple! {
+	()
+	(A 0)
+	(A 0, B 1)
+	(A 0, B 1, C 2)
+	(A 0, B 1, C 2, D 3)
+	(A 0, B 1, C 2, D 3, E 4)
+	(A 0, B 1, C 2, D 3, E 4, F 5)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10)
+	(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11)
+}
+
+impl<T: ToBits> ToBits for Option<T> {
+	#[inline]
+	fn to_bits<W: BitWrite>(&self, writer: &mut W) {
+		writer.write_bit(self.is_some());
+		if let Some(value) = self {
+			value.to_bits(writer);
+		}
+	}
+}
+impl<T: FromBits> FromBits for Option<T> {
+	#[inline]
+	fn from_bits<R: BitRead>(reader: &mut R) -> Result<Self, ReadError> {
+		reader.atomically(|reader| match reader.read_bit()? {
+			true => T::from_bits(reader).map(Some),
+			false => Ok(None),
+		})
+	}
+}
+
+impl<T: ToBits> ToBits for [T] {
+	#[inline]
+	fn to_bits<W: BitWrite>(&self, writer: &mut W) {
+		writer.write_varint64(self.len() as u64);
+		for value in self {
+			value.to_bits(writer);
+		}
+	}
+}
+impl<T: ToBits> ToBits for Vec<T> {
+	#[inline]
+	fn to_bits<W: BitWrite>(&self, writer: &mut W) {
+		self.as_slice().to_bits(writer);
+	}
+}
+impl<T: FromBits> FromBits for Vec<T> {
+	fn from_bits<R: BitRead>(reader: &mut R) -> Result<Self, ReadError> {
+		reader.atomically(|reader| {
+			let len = read_len(reader, usize::MAX)?;
+			// The length hasn't been checked against anything yet, so don't trust it with more than a small allocation
+			let max_capacity = MAX_PREALLOCATION / mem::size_of::<T>().max(1);
+			let mut values = Vec::with_capacity(len.min(max_capacity));
+			for _ in 0..len {
+				values.push(T::from_bits(reader)?);
+			}
+			Ok(values)
+		})
+	}
+}
+
+impl ToBits for str {
+	#[inline]
+	fn to_bits<W: BitWrite>(&self, writer: &mut W) {
+		writer.write_varint64(self.len() as u64);
+		writer.write_string(self);
+	}
+}
+impl ToBits for String {
+	#[inline]
+	fn to_bits<W: BitWrite>(&self, writer: &mut W) {
+		self.as_str().to_bits(writer);
+	}
+}
+impl FromBits for String {
+	#[inline]
+	fn from_bits<R: BitRead>(reader: &mut R) -> Result<Self, ReadError> {
+		reader.atomically(|reader| {
+			let len = read_len(reader, reader.bits_left().unwrap_or(usize::MAX) / 8)?;
+			reader.read_string(len)
+		})
+	}
+}
+
+/// The most bytes a `Vec` reserves for the elements its length prefix claims, before reading any of them.
+const MAX_PREALLOCATION: usize = 1 << 20;
+
+/// Reads the length of a `Vec` or `String`, which must be at most `max`.
+#[inline]
+fn read_len<R: BitRead>(reader: &mut R, max: usize) -> Result<usize, ReadError> {
+	let offset = reader.bits_read();
+	let len = reader.read_varint64()?;
+	usize::try_from(len)
+		.ok()
+		.filter(|len| *len <= max)
+		.ok_or(ReadError::InvalidValue { offset, value: len })
+}
+
+/// Used by the derive macros to pick the right method for a field's type.
+#[doc(hidden)]
+pub mod __private {
+	use super::*;
+
+	pub trait Bits: Sized {
+		fn encode_bits<W: BitWrite>(&self, writer: &mut W, bits: usize);
+
+		fn decode_bits<R: BitRead>(reader: &mut R, bits: usize) -> Result<Self, ReadError>;
+	}
+
+	pub trait Varint: Sized {
+		fn encode_varint<W: BitWrite>(&self, writer: &mut W);
+
+		fn decode_varint<R: BitRead>(reader: &mut R) -> Result<Self, ReadError>;
+	}
+
+	/// Keeps the low `bits` bits of an enum discriminant, which is all `#[bits(N)]` writes of it.
+	#[inline]
+	pub fn mask_discriminant(discriminant: u64, bits: usize) -> u64 {
+		if bits >= 64 {
+			discriminant
+		} else {
+			discriminant & ((1 << bits) - 1)
+		}
+	}
+}
@@ -0,0 +1,121 @@
+use std::{error::Error, fmt, io, str::Utf8Error};
+
+/// The reason a read from a [`BitVecReader`](crate::BitVecReader) failed.
+///
+/// Every variant carries the bit offset at which the read failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadError {
+	/// The read needed more bits than are left in the buffer.
+	OutOfBounds {
+		offset: usize,
+		requested: usize,
+		available: usize,
+	},
+
+	/// The bytes that were read are not valid UTF-8.
+	InvalidUtf8 { offset: usize, error: Utf8Error },
+
+	/// The requested number of bits can't be represented by the type being read.
+	OutOfRange {
+		offset: usize,
+		bits: usize,
+		max: usize,
+	},
+
+	/// A variable-length value was longer than the maximum length allowed for it.
+	LengthLimitExceeded { offset: usize, limit: usize },
+
+	/// A value was read that doesn't mean anything in the encoding being decoded, such as an unknown type tag.
+	InvalidValue { offset: usize, value: u64 },
+
+	/// The source a [`StreamBitReader`](crate::StreamBitReader) reads from returned an error.
+	Io { offset: usize, kind: io::ErrorKind },
+
+	/// An [`IncrementalReader`](crate::IncrementalReader) needs at least `needed_bits` more bits to be fed before
+	/// the read can succeed.
+	Incomplete { offset: usize, needed_bits: usize },
+}
+impl ReadError {
+	/// Returns the bit offset at which the read failed.
+	#[inline]
+	pub fn offset(&self) -> usize {
+		match self {
+			ReadError::OutOfBounds { offset, .. }
+			| ReadError::InvalidUtf8 { offset, .. }
+			| ReadError::OutOfRange { offset, .. }
+			| ReadError::LengthLimitExceeded { offset, .. }
+			| ReadError::InvalidValue { offset, .. }
+			| ReadError::Io { offset, .. }
+			| ReadError::Incomplete { offset, .. } => *offset,
+		}
+	}
+
+	/// Moves the offset forward by `bits`, for errors from a reader over part of a larger stream.
+	#[inline]
+	pub(crate) fn offset_by(mut self, bits: usize) -> ReadError {
+		match &mut self {
+			ReadError::OutOfBounds { offset, .. }
+			| ReadError::InvalidUtf8 { offset, .. }
+			| ReadError::OutOfRange { offset, .. }
+			| ReadError::LengthLimitExceeded { offset, .. }
+			| ReadError::InvalidValue { offset, .. }
+			| ReadError::Io { offset, .. }
+			| ReadError::Incomplete { offset, .. } => *offset += bits,
+		}
+		self
+	}
+}
+impl fmt::Display for ReadError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ReadError::OutOfBounds {
+				offset,
+				requested,
+				available,
+			} => write!(
+				f,
+				"tried to read {} bits at bit offset {}, but only {} bits are available",
+				requested, offset, available
+			),
+			ReadError::InvalidUtf8 { offset, error } => {
+				write!(
+					f,
+					"invalid UTF-8 in string at bit offset {}: {}",
+					offset, error
+				)
+			}
+			ReadError::OutOfRange { offset, bits, max } => write!(
+				f,
+				"tried to read {} bits at bit offset {} into a type of at most {} bits",
+				bits, offset, max
+			),
+			ReadError::LengthLimitExceeded { offset, limit } => write!(
+				f,
+				"value at bit offset {} exceeds the length limit of {}",
+				offset, limit
+			),
+			ReadError::InvalidValue { offset, value } => {
+				write!(f, "invalid value {} at bit offset {}", value, offset)
+			}
+			ReadError::Io { offset, kind } => {
+				write!(f, "I/O error at bit offset {}: {}", offset, kind)
+			}
+			ReadError::Incomplete {
+				offset,
+				needed_bits,
+			} => write!(
+				f,
+				"incomplete input at bit offset {}: {} more bits are needed",
+				offset, needed_bits
+			),
+		}
+	}
+}
+impl Error for ReadError {
+	fn source(&self) -> Option<&(dyn Error + 'static)> {
+		match self {
+			ReadError::InvalidUtf8 { error, .. } => Some(error),
+			_ => None,
+		}
+	}
+}
@@ -0,0 +1,264 @@
+//! Garry's Mod `net` library wire format.
+//!
+//! [`NetRead`] and [`NetWrite`] add the `net` functions that have their own encoding to
+//! [`BitVecReader<Lsb0>`] and [`BitVecWriter<Lsb0>`]. The engine writes LSB-first, so only [`Lsb0`] matches it
+//! bit for bit. The rest of the library maps onto the existing methods:
+//!
+//! | `net` function         | Reader                                      | Writer                                         |
+//! |------------------------|---------------------------------------------|------------------------------------------------|
+//! | `ReadBit`/`ReadBool`   | [`read_bit`](BitVecReader::read_bit)        | [`write_bit`](BitVecWriter::write_bit)         |
+//! | `ReadUInt`/`ReadInt`   | [`read_uint`](BitVecReader::read_uint)/[`read_int`](BitVecReader::read_int) | [`write_uint`](BitVecWriter::write_uint)/[`write_int`](BitVecWriter::write_int) |
+//! | `ReadFloat`            | [`read_float`](BitVecReader::read_float)    | [`write_float`](BitVecWriter::write_float)     |
+//! | `ReadDouble`           | [`read_double`](BitVecReader::read_double)  | [`write_double`](BitVecWriter::write_double)   |
+//! | `ReadString`           | [`read_nul_string`](BitVecReader::read_nul_string) | [`write_string_nul`](BitVecWriter::write_string_nul) |
+//! | `ReadData`             | [`read_bytes`](BitVecReader::read_bytes)    | [`write_bytes`](BitVecWriter::write_bytes)     |
+
+use crate::{BitVecReader, BitVecWriter, Lsb0, ReadError};
+
+pub const TYPE_NIL: u8 = 0;
+pub const TYPE_BOOL: u8 = 1;
+pub const TYPE_NUMBER: u8 = 3;
+pub const TYPE_STRING: u8 = 4;
+pub const TYPE_TABLE: u8 = 5;
+pub const TYPE_ENTITY: u8 = 9;
+pub const TYPE_VECTOR: u8 = 10;
+pub const TYPE_ANGLE: u8 = 11;
+pub const TYPE_MATRIX: u8 = 29;
+pub const TYPE_COLOR: u8 = 255;
+
+/// The deepest nesting of tables [`NetRead::read_table`] will decode, matching Lua's C stack limit.
+pub const MAX_TABLE_DEPTH: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Color {
+	pub r: u8,
+	pub g: u8,
+	pub b: u8,
+	pub a: u8,
+}
+
+/// A value that can be sent with `net.WriteType`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+	Bool(bool),
+	Number(f64),
+	String(String),
+	Table(Table),
+	/// An entity index, which is 0 for `NULL`.
+	Entity(u16),
+	Vector([f32; 3]),
+	Angle([f32; 3]),
+	Matrix([f32; 16]),
+	Color(Color),
+}
+
+/// A Lua table, as the key-value pairs in the order they were sent.
+pub type Table = Vec<(Value, Value)>;
+
+pub trait NetRead {
+	/// `net.ReadVector`
+	fn read_vector(&mut self) -> Result<[f32; 3], ReadError>;
+	/// `net.ReadAngle`
+	fn read_angle(&mut self) -> Result<[f32; 3], ReadError>;
+	/// `net.ReadNormal`
+	fn read_normal(&mut self) -> Result<[f32; 3], ReadError>;
+	/// `net.ReadColor`. If `read_alpha` is false, the alpha is not read and is set to 255.
+	fn read_color(&mut self, read_alpha: bool) -> Result<Color, ReadError>;
+	/// `net.ReadEntity`, returning the entity index.
+	fn read_entity(&mut self) -> Result<u16, ReadError>;
+	/// `net.ReadMatrix`
+	fn read_matrix(&mut self) -> Result<[f32; 16], ReadError>;
+	/// `net.ReadType`, returning `None` for `nil`.
+	fn read_type(&mut self) -> Result<Option<Value>, ReadError>;
+	/// `net.ReadTable`
+	fn read_table(&mut self) -> Result<Table, ReadError>;
+}
+
+pub trait NetWrite {
+	/// `net.WriteVector`
+	fn write_vector(&mut self, vector: [f32; 3]);
+	/// `net.WriteAngle`
+	fn write_angle(&mut self, angle: [f32; 3]);
+	/// `net.WriteNormal`
+	fn write_normal(&mut self, normal: [f32; 3]);
+	/// `net.WriteColor`
+	fn write_color(&mut self, color: Color, write_alpha: bool);
+	/// `net.WriteEntity`, taking the entity index.
+	fn write_entity(&mut self, entity: u16);
+	/// `net.WriteMatrix`
+	fn write_matrix(&mut self, matrix: [f32; 16]);
+	/// `net.WriteType`, writing `nil` for `None`.
+	fn write_type(&mut self, value: Option<&Value>);
+	/// `net.WriteTable`
+	fn write_table(&mut self, table: &[(Value, Value)]);
+}
+
+impl NetRead for BitVecReader<Lsb0> {
+	fn read_vector(&mut self) -> Result<[f32; 3], ReadError> {
+		self.read_bit_vec3_coord()
+	}
+
+	fn read_angle(&mut self) -> Result<[f32; 3], ReadError> {
+		self.read_bit_vec3_coord()
+	}
+
+	fn read_normal(&mut self) -> Result<[f32; 3], ReadError> {
+		self.read_bit_vec3_normal()
+	}
+
+	fn read_color(&mut self, read_alpha: bool) -> Result<Color, ReadError> {
+		self.atomically(|reader| {
+			Ok(Color {
+				r: reader.read_byte()?,
+				g: reader.read_byte()?,
+				b: reader.read_byte()?,
+				a: if read_alpha { reader.read_byte()? } else { 255 },
+			})
+		})
+	}
+
+	fn read_entity(&mut self) -> Result<u16, ReadError> {
+		self.read_uint(16)
+	}
+
+	fn read_matrix(&mut self) -> Result<[f32; 16], ReadError> {
+		self.atomically(|reader| {
+			let mut matrix = [0.; 16];
+			for component in matrix.iter_mut() {
+				*component = reader.read_float()?;
+			}
+			Ok(matrix)
+		})
+	}
+
+	fn read_type(&mut self) -> Result<Option<Value>, ReadError> {
+		self.atomically(|reader| read_type(reader, 0))
+	}
+
+	fn read_table(&mut self) -> Result<Table, ReadError> {
+		self.atomically(|reader| read_table(reader, 0))
+	}
+}
+
+fn read_type(reader: &mut BitVecReader<Lsb0>, depth: usize) -> Result<Option<Value>, ReadError> {
+	let offset = reader.cursor;
+	let value = match reader.read_byte()? {
+		TYPE_NIL => return Ok(None),
+		TYPE_BOOL => Value::Bool(reader.read_bit()?),
+		TYPE_NUMBER => Value::Number(reader.read_double()?),
+		TYPE_STRING => Value::String(reader.read_nul_string()?),
+		TYPE_TABLE => Value::Table(read_table(reader, depth + 1)?),
+		TYPE_ENTITY => Value::Entity(reader.read_entity()?),
+		TYPE_VECTOR => Value::Vector(reader.read_vector()?),
+		TYPE_ANGLE => Value::Angle(reader.read_angle()?),
+		TYPE_MATRIX => Value::Matrix(reader.read_matrix()?),
+		TYPE_COLOR => Value::Color(reader.read_color(true)?),
+		ty => {
+			return Err(ReadError::InvalidValue {
+				offset,
+				value: ty as u64,
+			})
+		}
+	};
+	Ok(Some(value))
+}
+
+fn read_table(reader: &mut BitVecReader<Lsb0>, depth: usize) -> Result<Table, ReadError> {
+	if depth >= MAX_TABLE_DEPTH {
+		return Err(ReadError::LengthLimitExceeded {
+			offset: reader.cursor,
+			limit: MAX_TABLE_DEPTH,
+		});
+	}
+	let mut table = Table::new();
+	while let Some(key) = read_type(reader, depth)? {
+		// Like `net.ReadTable`, a nil value just doesn't set the key
+		if let Some(value) = read_type(reader, depth)? {
+			table.push((key, value));
+		}
+	}
+	Ok(table)
+}
+
+impl NetWrite for BitVecWriter<Lsb0> {
+	fn write_vector(&mut self, vector: [f32; 3]) {
+		self.write_bit_vec3_coord(vector);
+	}
+
+	fn write_angle(&mut self, angle: [f32; 3]) {
+		self.write_bit_vec3_coord(angle);
+	}
+
+	fn write_normal(&mut self, normal: [f32; 3]) {
+		self.write_bit_vec3_normal(normal);
+	}
+
+	fn write_color(&mut self, color: Color, write_alpha: bool) {
+		self.write_byte(color.r);
+		self.write_byte(color.g);
+		self.write_byte(color.b);
+		if write_alpha {
+			self.write_byte(color.a);
+		}
+	}
+
+	fn write_entity(&mut self, entity: u16) {
+		self.write_uint(entity, 16);
+	}
+
+	fn write_matrix(&mut self, matrix: [f32; 16]) {
+		for component in matrix.iter() {
+			self.write_float(*component);
+		}
+	}
+
+	fn write_type(&mut self, value: Option<&Value>) {
+		match value {
+			None => self.write_byte(TYPE_NIL),
+			Some(Value::Bool(bool)) => {
+				self.write_byte(TYPE_BOOL);
+				self.write_bit(*bool);
+			}
+			Some(Value::Number(number)) => {
+				self.write_byte(TYPE_NUMBER);
+				self.write_double(*number);
+			}
+			Some(Value::String(string)) => {
+				self.write_byte(TYPE_STRING);
+				self.write_string_nul(string);
+			}
+			Some(Value::Table(table)) => {
+				self.write_byte(TYPE_TABLE);
+				self.write_table(table);
+			}
+			Some(Value::Entity(entity)) => {
+				self.write_byte(TYPE_ENTITY);
+				self.write_entity(*entity);
+			}
+			Some(Value::Vector(vector)) => {
+				self.write_byte(TYPE_VECTOR);
+				self.write_vector(*vector);
+			}
+			Some(Value::Angle(angle)) => {
+				self.write_byte(TYPE_ANGLE);
+				self.write_angle(*angle);
+			}
+			Some(Value::Matrix(matrix)) => {
+				self.write_byte(TYPE_MATRIX);
+				self.write_matrix(*matrix);
+			}
+			Some(Value::Color(color)) => {
+				self.write_byte(TYPE_COLOR);
+				self.write_color(*color, true);
+			}
+		}
+	}
+
+	fn write_table(&mut self, table: &[(Value, Value)]) {
+		for (key, value) in table {
+			self.write_type(Some(key));
+			self.write_type(Some(value));
+		}
+		self.write_type(None);
+	}
+}
@@ -0,0 +1,142 @@
+use bitvec::{field::BitField, order::BitOrder, slice::BitSlice, vec::BitVec};
+
+use crate::{read::LoadBits, BitVecReader, ReadError};
+
+/// Reads messages from bytes that arrive in pieces, such as from a TCP socket.
+///
+/// Bytes are appended with [`feed`](IncrementalReader::feed), and read with [`decode`](IncrementalReader::decode),
+/// which runs a decoder against the [`BitVecReader`] underneath. A decode that runs out of bits fails with
+/// [`ReadError::Incomplete`] rather than [`ReadError::OutOfBounds`] and leaves the cursor where it was, so it can be
+/// retried once more bytes have been fed. Whatever has been decoded successfully stays read. A decode that needs
+/// more than [`max_bits`](IncrementalReader::max_bits) bits still fails with [`ReadError::OutOfBounds`], so a
+/// corrupt length fails fast instead of waiting for bytes that would never be enough.
+///
+/// A message decoded in several steps can be retried as a whole by setting a
+/// [`checkpoint`](IncrementalReader::checkpoint) before it and calling [`rollback`](IncrementalReader::rollback) if
+/// a step is incomplete. Error offsets are counted from the first byte fed.
+#[derive(Debug, Clone)]
+pub struct IncrementalReader<O: BitOrder> {
+	buffer: BitVecReader<O>,
+	/// The number of bits dropped from the front of the buffer.
+	dropped: usize,
+	/// The position to return to on [`rollback`](IncrementalReader::rollback), counted from the first byte fed.
+	checkpoint: Option<usize>,
+	/// The most bits a decode can need from where it starts for running out of them to be incomplete.
+	max_bits: usize,
+}
+impl<O: BitOrder> Default for IncrementalReader<O>
+where
+	BitSlice<O, u8>: BitField + LoadBits<O>,
+{
+	#[inline]
+	fn default() -> Self {
+		IncrementalReader {
+			buffer: BitVecReader::new(BitVec::new()),
+			dropped: 0,
+			checkpoint: None,
+			max_bits: BitSlice::<O, u8>::MAX_BITS,
+		}
+	}
+}
+impl<O: BitOrder> IncrementalReader<O>
+where
+	BitSlice<O, u8>: BitField + LoadBits<O>,
+{
+	#[inline]
+	pub fn new() -> IncrementalReader<O> {
+		IncrementalReader::default()
+	}
+
+	/// Creates a reader whose decodes can need at most `max_bits` bits from where they start. A decode that runs out
+	/// of bits needing more fails with [`ReadError::OutOfBounds`] rather than [`ReadError::Incomplete`].
+	#[inline]
+	pub fn with_max_bits(max_bits: usize) -> IncrementalReader<O> {
+		IncrementalReader {
+			max_bits,
+			..IncrementalReader::default()
+		}
+	}
+
+	/// Returns the most bits a decode can need from where it starts.
+	#[inline]
+	pub fn max_bits(&self) -> usize {
+		self.max_bits
+	}
+
+	/// Appends `bytes` to the buffer, dropping the bytes that have been read and aren't needed for a rollback.
+	pub fn feed<B: AsRef<[u8]>>(&mut self, bytes: B) {
+		let keep = match self.checkpoint {
+			Some(checkpoint) => checkpoint - self.dropped,
+			None => self.buffer.cursor,
+		};
+		let drop = keep / 8;
+
+		let mut buffer = std::mem::take(&mut self.buffer.bitvec).into_vec();
+		buffer.drain(..drop);
+		buffer.extend_from_slice(bytes.as_ref());
+		self.buffer.bitvec = BitVec::from_vec(buffer);
+		self.buffer.cursor -= drop * 8;
+		self.dropped += drop * 8;
+	}
+
+	/// Returns the number of bits read from the first byte fed.
+	#[inline]
+	pub fn bits_read(&self) -> usize {
+		self.dropped + self.buffer.cursor
+	}
+
+	/// Returns the number of bits that have been fed but not read yet.
+	#[inline]
+	pub fn bits_buffered(&self) -> usize {
+		self.buffer.bits_left()
+	}
+
+	/// Runs `decode` against the buffer. If it fails, the cursor is left where it was, and running out of bits is
+	/// returned as [`ReadError::Incomplete`], unless the decode needed more than
+	/// [`max_bits`](IncrementalReader::max_bits) bits.
+	pub fn decode<T>(
+		&mut self,
+		decode: impl FnOnce(&mut BitVecReader<O>) -> Result<T, ReadError>,
+	) -> Result<T, ReadError> {
+		let dropped = self.dropped;
+		let start = self.buffer.cursor;
+		let max_bits = self.max_bits;
+		self.buffer.atomically(decode).map_err(|error| {
+			let error = match error {
+				ReadError::OutOfBounds {
+					offset,
+					requested,
+					available,
+				} if offset.saturating_sub(start).saturating_add(requested) <= max_bits => {
+					ReadError::Incomplete {
+						offset,
+						needed_bits: requested - available,
+					}
+				}
+				error => error,
+			};
+			error.offset_by(dropped)
+		})
+	}
+
+	/// Marks the current position to return to with [`rollback`](IncrementalReader::rollback), replacing any
+	/// earlier checkpoint. The bytes after it are kept until it is committed or rolled back to.
+	#[inline]
+	pub fn checkpoint(&mut self) {
+		self.checkpoint = Some(self.bits_read());
+	}
+
+	/// Moves the cursor back to the checkpoint and clears it. Does nothing if there is no checkpoint.
+	#[inline]
+	pub fn rollback(&mut self) {
+		if let Some(checkpoint) = self.checkpoint.take() {
+			self.buffer.cursor = checkpoint - self.dropped;
+		}
+	}
+
+	/// Clears the checkpoint, keeping everything read since it.
+	#[inline]
+	pub fn commit(&mut self) {
+		self.checkpoint = None;
+	}
+}
@@ -4,16 +4,46 @@ extern crate test;
 
 #[cfg(test)]
 mod tests;
+#[cfg(test)]
+extern crate self as bitvecbuf;
 
+mod cached;
+mod coord;
+pub mod encode;
+mod error;
+pub mod gmod;
+mod incremental;
 mod read;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod stream;
 mod write;
 
-pub use read::BitVecReader;
-pub use write::BitVecWriter;
+pub use cached::{CacheOrder, CachedBitReader};
+pub use encode::{FromBits, ToBits};
+pub use error::ReadError;
+pub use incremental::IncrementalReader;
+pub use read::{BitRead, BitSliceReader, BitVecReader, LoadBits, ReaderCheckpoint};
+pub use stream::{StreamBitReader, StreamBitWriter};
+pub use write::{
+	ArrayBitWriter, BitSliceWriter, BitVecWriter, BitWrite, Placeholder, StoreBits,
+	WriterCheckpoint,
+};
+
+#[cfg(feature = "derive")]
+pub use bitvecbuf_derive::{BitDecode, BitEncode};
 
 pub use bitvec;
 pub use bitvec::order::{LocalBits, Lsb0, Msb0};
 
+/// The maximum number of bytes a 32-bit varint can be encoded in.
+pub const MAX_VARINT32_BYTES: usize = 5;
+/// The maximum number of bytes a 64-bit varint can be encoded in.
+pub const MAX_VARINT64_BYTES: usize = 10;
+
+/// The delta that marks the end of a list of delta-encoded field indices.
+pub(crate) const FIELD_INDEX_END: u32 = 0xFFF;
+
 pub trait BitCount {
 	const BIT_COUNT: usize;
 }
@@ -1,6 +1,11 @@
-use std::{ops::Range, string::FromUtf8Error};
+use std::{
+	borrow::Cow,
+	io,
+	ops::{Deref, DerefMut, Range},
+};
 
 use bitvec::{
+	domain::Domain,
 	field::BitField,
 	macros::internal::funty::{IsFloat, IsNumber, IsSigned, IsUnsigned},
 	mem::BitMemory,
@@ -9,8 +14,20 @@ use bitvec::{
 	vec::BitVec,
 };
 
-use crate::BitCount;
+use crate::{
+	coord::{
+		COORD_FRACTIONAL_BITS, COORD_FRACTIONAL_BITS_MP_LOWPRECISION, COORD_INTEGER_BITS,
+		COORD_INTEGER_BITS_MP, COORD_RESOLUTION, COORD_RESOLUTION_LOWPRECISION,
+		NORMAL_FRACTIONAL_BITS, NORMAL_RESOLUTION,
+	},
+	BitCount, CacheOrder, CachedBitReader, FromBits, ReadError, FIELD_INDEX_END,
+	MAX_VARINT32_BYTES, MAX_VARINT64_BYTES,
+};
 
+/// Reads bits from a [`BitVec`], starting at `cursor`.
+///
+/// Every read either succeeds and advances the cursor past what it read, or fails with a [`ReadError`]
+/// and leaves the cursor where it was.
 #[derive(Debug, Clone)]
 pub struct BitVecReader<O: BitOrder> {
 	pub cursor: usize,
@@ -43,218 +60,1003 @@ where
 		self.bitvec
 	}
 
+	/// Saves the cursor, returning a guard that restores it when dropped unless it is
+	/// [`commit`](ReaderCheckpoint::commit)ted. The reader can be used through the guard in the meantime.
 	#[inline]
-	pub fn bits_len(&self) -> usize {
-		self.bitvec.len()
+	pub fn checkpoint(&mut self) -> ReaderCheckpoint<'_, O> {
+		ReaderCheckpoint {
+			cursor: self.cursor,
+			committed: false,
+			reader: self,
+		}
 	}
 
-	/// Returns the number of bytes in the bitvec, rounded up to the byte.
+	/// Reads a `T` with its [`FromBits`] implementation. If this fails, the cursor is left where it was.
+	///
+	/// This shadows [`io::Read::read`], which can still be called as `io::Read::read(&mut reader, buf)`.
 	#[inline]
-	pub fn bytes_len(&self) -> usize {
-		((self.bits_len() as f32) / 8.).ceil() as usize
+	pub fn read<T: FromBits>(&mut self) -> Result<T, ReadError> {
+		self.atomically(T::from_bits)
 	}
+}
 
+/// A saved [`BitVecReader`] cursor, returned by [`BitVecReader::checkpoint`].
+///
+/// Dropping the guard moves the cursor back to where it was when the checkpoint was made, unless
+/// [`commit`](ReaderCheckpoint::commit) has been called.
+#[derive(Debug)]
+#[must_use = "the cursor is restored as soon as the checkpoint is dropped"]
+pub struct ReaderCheckpoint<'a, O: BitOrder> {
+	reader: &'a mut BitVecReader<O>,
+	cursor: usize,
+	committed: bool,
+}
+impl<O: BitOrder> ReaderCheckpoint<'_, O> {
+	/// Keeps everything read since the checkpoint.
 	#[inline]
-	pub fn bits_left(&self) -> usize {
-		self.bitvec.len() - self.cursor
+	pub fn commit(mut self) {
+		self.committed = true;
 	}
 
+	/// Moves the cursor back to the checkpoint now.
 	#[inline]
-	pub fn bytes_left(&self) -> usize {
-		((self.bits_left() as f32) / 8.).ceil() as usize
-	}
+	pub fn rollback(self) {}
+}
+impl<O: BitOrder> Deref for ReaderCheckpoint<'_, O> {
+	type Target = BitVecReader<O>;
 
 	#[inline]
-	pub fn bits_read(&self) -> usize {
-		self.cursor
+	fn deref(&self) -> &BitVecReader<O> {
+		self.reader
 	}
-
+}
+impl<O: BitOrder> DerefMut for ReaderCheckpoint<'_, O> {
 	#[inline]
-	pub fn bytes_read(&self) -> usize {
-		((self.bits_read() as f32) / 8.).ceil() as usize
+	fn deref_mut(&mut self) -> &mut BitVecReader<O> {
+		self.reader
 	}
-
+}
+impl<O: BitOrder> Drop for ReaderCheckpoint<'_, O> {
 	#[inline]
-	pub fn advance(&mut self, n: usize) {
-		self.cursor += n;
+	fn drop(&mut self) {
+		if !self.committed {
+			self.reader.cursor = self.cursor;
+		}
 	}
+}
 
+/// Reads bits from a borrowed [`BitSlice`], starting at `cursor`.
+///
+/// This has the same API and error behaviour as [`BitVecReader`], but reads straight from the caller's memory.
+/// Its `_borrowed` reads return data borrowed from that memory when the read starts on a byte boundary.
+#[derive(Debug, Clone, Copy)]
+pub struct BitSliceReader<'a, O: BitOrder> {
+	pub cursor: usize,
+	pub bitslice: &'a BitSlice<O, u8>,
+}
+impl<'a, O: BitOrder> BitSliceReader<'a, O>
+where
+	BitSlice<O, u8>: BitField + LoadBits<O>,
+{
 	#[inline]
-	pub fn rewind(&mut self, n: usize) {
-		self.cursor -= n;
+	pub fn new(bitslice: &'a BitSlice<O, u8>) -> BitSliceReader<'a, O> {
+		BitSliceReader {
+			cursor: 0,
+			bitslice,
+		}
 	}
 
 	#[inline]
-	fn check_range(&self, max: usize) -> Option<Range<usize>> {
-		let max = self.cursor + max;
-		if self.bitvec.len() < max {
-			None
-		} else {
-			Some(self.cursor..max)
-		}
+	pub fn from_bytes(bytes: &'a [u8]) -> BitSliceReader<'a, O> {
+		BitSliceReader::new(BitSlice::from_slice(bytes).unwrap())
 	}
 
 	#[inline]
-	fn read_bits(&self, max: usize) -> Option<&BitSlice<O, u8>>
-	where
-		BitSlice<O, u8>: BitField + LoadBits<O>,
-	{
-		Some(&self.bitvec[self.check_range(max)?])
+	pub fn into_bitslice(self) -> &'a BitSlice<O, u8> {
+		self.bitslice
 	}
 
+	/// Returns the bytes covering `range`, if it starts and ends on byte boundaries of the underlying memory.
 	#[inline]
-	pub fn read_bit(&mut self) -> Option<bool> {
-		let bit = self.bitvec.get(self.cursor).map(|bit| *bit);
-		self.advance(1);
-		bit
+	fn borrow_bytes(&self, range: Range<usize>) -> Option<&'a [u8]> {
+		aligned_bytes(&self.bitslice[range])
+	}
+
+	/// Reads `len` bytes, borrowing them if the cursor is byte-aligned and copying them otherwise.
+	pub fn read_bytes_borrowed(&mut self, len: usize) -> Result<Cow<'a, [u8]>, ReadError> {
+		let range = self.check_range(byte_bits(len))?;
+		let bytes = match self.borrow_bytes(range.clone()) {
+			Some(bytes) => Cow::Borrowed(bytes),
+			None => Cow::Owned(self.load_bytes(range)),
+		};
+		self.advance(len * 8);
+		Ok(bytes)
+	}
+
+	/// Reads a string of `bytes` bytes, borrowing it if the cursor is byte-aligned and copying it otherwise.
+	pub fn read_string_borrowed(&mut self, bytes: usize) -> Result<Cow<'a, str>, ReadError> {
+		let range = self.check_range(byte_bits(bytes))?;
+		let string = match self.borrow_bytes(range.clone()) {
+			Some(string) => Cow::Borrowed(self.utf8_borrowed(string)?),
+			None => Cow::Owned(self.utf8(self.load_bytes(range))?),
+		};
+		self.advance(bytes * 8);
+		Ok(string)
+	}
+
+	/// Reads a nul-terminated string, borrowing it if the cursor is byte-aligned and copying it otherwise.
+	pub fn read_nul_string_borrowed(&mut self) -> Result<Cow<'a, str>, ReadError> {
+		let string = self.load_nul_string()?;
+		let len = string.len();
+		let string = match self.borrow_bytes(self.cursor..self.cursor + len * 8) {
+			Some(string) => Cow::Borrowed(self.utf8_borrowed(string)?),
+			None => Cow::Owned(self.utf8(string)?),
+		};
+		self.advance((len + 1) * 8);
+		Ok(string)
 	}
 
 	#[inline]
-	pub fn read_byte(&mut self) -> Option<u8> {
-		let byte = self.read_bits(8)?;
-		let byte = Some(byte.load_bits());
-		self.advance(8);
-		byte
+	fn utf8_borrowed(&self, bytes: &'a [u8]) -> Result<&'a str, ReadError> {
+		std::str::from_utf8(bytes).map_err(|error| ReadError::InvalidUtf8 {
+			offset: self.cursor,
+			error,
+		})
 	}
+}
+
+/// Returns the bytes under `bits`, if it starts and ends on byte boundaries of the underlying memory.
+#[inline]
+fn aligned_bytes<O: BitOrder>(bits: &BitSlice<O, u8>) -> Option<&[u8]> {
+	match bits.domain() {
+		Domain::Region {
+			head: None,
+			body,
+			tail: None,
+		} => Some(body),
+		_ => None,
+	}
+}
+
+/// The reads shared by every reader and the [`BitRead`] defaults, which are built on `read_bit`, `read_uint`,
+/// `read_bytes`, `atomically` and the varint reader `$read_varint`.
+macro_rules! shared_reads {
+	( $vis:vis, $read_varint:path ) => {
+		/// Reads a protobuf-style varint, failing with [`ReadError::LengthLimitExceeded`] if it isn't terminated
+		/// within [`MAX_VARINT32_BYTES`](crate::MAX_VARINT32_BYTES) bytes.
+		#[inline]
+		$vis fn read_varint32(&mut self) -> Result<u32, ReadError> {
+			$read_varint(self, MAX_VARINT32_BYTES).map(|varint| varint as u32)
+		}
+
+		/// Reads a protobuf-style varint, failing with [`ReadError::LengthLimitExceeded`] if it isn't terminated
+		/// within [`MAX_VARINT64_BYTES`](crate::MAX_VARINT64_BYTES) bytes.
+		#[inline]
+		$vis fn read_varint64(&mut self) -> Result<u64, ReadError> {
+			$read_varint(self, MAX_VARINT64_BYTES)
+		}
+
+		/// Reads a zigzag-encoded [`read_varint32`](Self::read_varint32).
+		#[inline]
+		$vis fn read_signed_varint32(&mut self) -> Result<i32, ReadError> {
+			self.read_varint32().map(zigzag_decode32)
+		}
+
+		/// Reads a zigzag-encoded [`read_varint64`](Self::read_varint64).
+		#[inline]
+		$vis fn read_signed_varint64(&mut self) -> Result<i64, ReadError> {
+			self.read_varint64().map(zigzag_decode64)
+		}
+
+		/// Reads a Source engine `UBitVar`: a 2-bit selector followed by a 4, 8, 12 or 32-bit value.
+		$vis fn read_ubitvar(&mut self) -> Result<u32, ReadError> {
+			self.atomically(|reader| {
+				let bits = match reader.read_uint::<u8>(2)? {
+					0 => 4,
+					1 => 8,
+					2 => 12,
+					_ => 32,
+				};
+				reader.read_uint(bits)
+			})
+		}
 
-	pub fn read_bytes(&mut self, len: usize) -> Option<Vec<u8>> {
-		let len_bits = len * 8;
-		let range = self.check_range(len_bits)?;
+		/// Reads a Source engine entity property index, delta-encoded against `last_index`.
+		///
+		/// `new_way` selects the encoding with the extra short forms for small deltas. Returns `None` when the end
+		/// marker is read, and fails with [`ReadError::InvalidValue`] if the index would be past `i32::MAX`.
+		$vis fn read_field_index(
+			&mut self,
+			last_index: i32,
+			new_way: bool,
+		) -> Result<Option<i32>, ReadError> {
+			self.atomically(|reader| {
+				let offset = reader.bits_read();
+				let index = |delta: u32| {
+					last_index
+						.checked_add(1 + delta as i32)
+						.ok_or(ReadError::InvalidValue {
+							offset,
+							value: delta as u64,
+						})
+				};
+				if new_way && reader.read_bit()? {
+					return index(0).map(Some);
+				}
+				let delta = if new_way && reader.read_bit()? {
+					reader.read_uint::<u32>(3)?
+				} else {
+					let delta = reader.read_uint::<u32>(7)?;
+					match delta & (32 | 64) {
+						32 => (delta & !96) | (reader.read_uint::<u32>(2)? << 5),
+						64 => (delta & !96) | (reader.read_uint::<u32>(4)? << 5),
+						96 => (delta & !96) | (reader.read_uint::<u32>(7)? << 5),
+						_ => delta,
+					}
+				};
+				if delta == FIELD_INDEX_END {
+					Ok(None)
+				} else {
+					index(delta).map(Some)
+				}
+			})
+		}
+
+		/// Reads a coordinate encoded with Source engine's `WriteBitCoord`.
+		$vis fn read_bit_coord(&mut self) -> Result<f32, ReadError> {
+			self.atomically(|reader| {
+				let has_int = reader.read_bit()?;
+				let has_fract = reader.read_bit()?;
+				if !has_int && !has_fract {
+					return Ok(0.);
+				}
+				let sign = reader.read_bit()?;
+				let int = if has_int {
+					reader.read_uint::<u32>(COORD_INTEGER_BITS)? + 1
+				} else {
+					0
+				};
+				let fract = if has_fract {
+					reader.read_uint::<u32>(COORD_FRACTIONAL_BITS)?
+				} else {
+					0
+				};
+				let value = (int as f64 + fract as f64 * COORD_RESOLUTION) as f32;
+				Ok(if sign { -value } else { value })
+			})
+		}
 
-		let mut bytes = Vec::with_capacity(len);
-		for i in range.step_by(8) {
-			let byte: u8 = self.bitvec[i..i+8].load_bits();
-			bytes.push(byte);
+		/// Reads a coordinate encoded with Source engine's `WriteBitCoordMP`.
+		///
+		/// `integral` and `low_precision` must match the flags the coordinate was written with.
+		$vis fn read_bit_coord_mp(
+			&mut self,
+			integral: bool,
+			low_precision: bool,
+		) -> Result<f32, ReadError> {
+			self.atomically(|reader| {
+				let int_bits = if reader.read_bit()? {
+					COORD_INTEGER_BITS_MP
+				} else {
+					COORD_INTEGER_BITS
+				};
+				let has_int = reader.read_bit()?;
+				if integral {
+					if !has_int {
+						return Ok(0.);
+					}
+					let sign = reader.read_bit()?;
+					let value = (reader.read_uint::<u32>(int_bits)? + 1) as f32;
+					Ok(if sign { -value } else { value })
+				} else {
+					let sign = reader.read_bit()?;
+					let int = if has_int {
+						reader.read_uint::<u32>(int_bits)? + 1
+					} else {
+						0
+					};
+					let (fract_bits, resolution) = if low_precision {
+						(
+							COORD_FRACTIONAL_BITS_MP_LOWPRECISION,
+							COORD_RESOLUTION_LOWPRECISION,
+						)
+					} else {
+						(COORD_FRACTIONAL_BITS, COORD_RESOLUTION)
+					};
+					let fract = reader.read_uint::<u32>(fract_bits)?;
+					let value = (int as f64 + fract as f64 * resolution) as f32;
+					Ok(if sign { -value } else { value })
+				}
+			})
 		}
 
-		self.advance(len_bits);
+		/// Reads a normal component encoded with Source engine's `WriteBitNormal`.
+		$vis fn read_bit_normal(&mut self) -> Result<f32, ReadError> {
+			self.atomically(|reader| {
+				let sign = reader.read_bit()?;
+				let fract = reader.read_uint::<u32>(NORMAL_FRACTIONAL_BITS)?;
+				let value = (fract as f64 * NORMAL_RESOLUTION) as f32;
+				Ok(if sign { -value } else { value })
+			})
+		}
 
-		Some(bytes)
+		/// Reads an angle in degrees encoded with Source engine's `WriteBitAngle` using `bits` bits, for `bits` in
+		/// `1..=32`.
+		$vis fn read_bit_angle(&mut self, bits: usize) -> Result<f32, ReadError> {
+			if bits == 0 || bits > 32 {
+				return Err(ReadError::OutOfRange {
+					offset: self.bits_read(),
+					bits,
+					max: 32,
+				});
+			}
+			let shift = (1_u64 << bits) as f32;
+			let angle = self.read_uint::<u32>(bits)?;
+			Ok((angle as f32 as f64 * (360.0 / shift as f64)) as f32)
+		}
+
+		/// Reads a vector encoded with Source engine's `WriteBitVec3Coord`.
+		$vis fn read_bit_vec3_coord(&mut self) -> Result<[f32; 3], ReadError> {
+			self.atomically(|reader| {
+				let flags = [reader.read_bit()?, reader.read_bit()?, reader.read_bit()?];
+				let mut vec = [0.; 3];
+				for (component, flag) in vec.iter_mut().zip(flags) {
+					if flag {
+						*component = reader.read_bit_coord()?;
+					}
+				}
+				Ok(vec)
+			})
+		}
+
+		/// Reads a unit vector encoded with Source engine's `WriteBitVec3Normal`.
+		$vis fn read_bit_vec3_normal(&mut self) -> Result<[f32; 3], ReadError> {
+			self.atomically(|reader| {
+				let has_x = reader.read_bit()?;
+				let has_y = reader.read_bit()?;
+				let x = if has_x { reader.read_bit_normal()? } else { 0. };
+				let y = if has_y { reader.read_bit_normal()? } else { 0. };
+				let z_negative = reader.read_bit()?;
+				let xy = x * x + y * y;
+				let z = if xy < 1. { (1. - xy).sqrt() } else { 0. };
+				Ok([x, y, if z_negative { -z } else { z }])
+			})
+		}
+
+		/// # Safety
+		///
+		/// The bytes read must be valid UTF-8.
+		#[inline]
+		$vis unsafe fn read_string_unchecked(&mut self, bytes: usize) -> Result<String, ReadError> {
+			self.read_bytes(bytes)
+				.map(|bytes| String::from_utf8_unchecked(bytes))
+		}
+
+		#[inline]
+		$vis fn read_string_lossy(&mut self, bytes: usize) -> Result<String, ReadError> {
+			self.read_bytes(bytes)
+				.map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+		}
+	};
+}
+pub(crate) use shared_reads;
+
+/// Reads bits, so that a decoder can be written once and run on any reader: the crate's readers over owned
+/// buffers, borrowed slices and streams, or a test double.
+///
+/// Only the cursor queries, [`read_bit`](BitRead::read_bit) and [`read_uint`](BitRead::read_uint) have to be
+/// implemented. Every other read has a default built on those, which the crate's readers override with their own.
+///
+/// The crate's readers leave the cursor where it was when a read fails, including a read made up of several others
+/// with [`atomically`](BitRead::atomically). Its default can't move the cursor back, so an implementation that
+/// doesn't override it may be left partway through a failed read.
+pub trait BitRead {
+	/// Returns the number of bits read.
+	fn bits_read(&self) -> usize;
+
+	/// Returns the number of bytes read, rounded up to the byte.
+	#[inline]
+	fn bytes_read(&self) -> usize {
+		(self.bits_read() + 7) / 8
+	}
+
+	/// Returns the number of bits left to read, or `None` if that isn't known yet, such as for a stream that
+	/// hasn't reached its end.
+	fn bits_left(&self) -> Option<usize>;
+
+	fn read_bit(&mut self) -> Result<bool, ReadError>;
+
+	fn read_uint<N>(&mut self, bits: usize) -> Result<N, ReadError>
+	where
+		N: BitMemory + IsNumber + IsUnsigned;
+
+	/// Runs a read made up of several smaller reads, moving the cursor back if any of them fail.
+	#[inline]
+	fn atomically<T>(
+		&mut self,
+		read: impl FnOnce(&mut Self) -> Result<T, ReadError>,
+	) -> Result<T, ReadError> {
+		read(self)
 	}
 
+	/// Reads a `T` with its [`FromBits`] implementation.
 	#[inline]
-	pub fn read_uint<N>(&mut self, bits: usize) -> Option<N>
+	fn read<T: FromBits>(&mut self) -> Result<T, ReadError>
 	where
-		N: BitMemory + IsNumber + IsUnsigned,
+		Self: Sized,
 	{
-		let uint = self.read_bits(bits)?;
-		let uint: Option<N> = Some(uint.load_bits());
-		self.advance(bits);
-		uint
+		self.atomically(T::from_bits)
+	}
+
+	#[inline]
+	fn read_byte(&mut self) -> Result<u8, ReadError> {
+		self.read_uint(8)
+	}
+
+	fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>, ReadError> {
+		self.atomically(|reader| (0..len).map(|_| reader.read_byte()).collect())
 	}
 
 	#[inline]
-	pub fn read_int<N>(&mut self, bits: usize) -> Option<N>
+	fn read_int<N>(&mut self, bits: usize) -> Result<N, ReadError>
 	where
 		N: FromBitMemory + IsNumber + IsSigned,
 	{
-		let int = self.read_bits(bits)?;
-		let int: Option<N> = Some(int.load_bits())
-			.map(|int: <N as FromBitMemory>::Unsigned| N::from_bitmemory(int, bits));
-		self.advance(bits);
-		int
+		self.read_uint(bits)
+			.map(|uint| N::from_bitmemory(uint, bits))
 	}
 
-	#[inline]
-	pub fn read_float(&mut self) -> Option<f32> {
-		let float = self.read_bits(f32::BIT_COUNT)?;
-		let float: Option<f32> = Some(float.load_bits())
-			.map(|float: <f32 as FromBitMemory>::Unsigned| f32::from_bitmemory(float, f32::BIT_COUNT));
-		self.advance(f32::BIT_COUNT);
-		float
+	/// Reads `out.len()` consecutive `bits`-bit uints into `out`, for `bits` in `1..=32`.
+	fn read_uints_into(&mut self, out: &mut [u32], bits: usize) -> Result<(), ReadError> {
+		self.atomically(|reader| {
+			for uint in out.iter_mut() {
+				*uint = reader.read_uint(bits)?;
+			}
+			Ok(())
+		})
 	}
 
-	#[cfg(target_pointer_width = "64")]
 	#[inline]
-	pub fn read_double(&mut self) -> Option<f64> {
-		let float = self.read_bits(f64::BIT_COUNT)?;
-		let float: Option<f64> = Some(float.load_bits())
-			.map(|float: <f64 as FromBitMemory>::Unsigned| f64::from_bitmemory(float, f64::BIT_COUNT));
-		self.advance(f64::BIT_COUNT);
-		float
+	fn read_float(&mut self) -> Result<f32, ReadError> {
+		self.read_uint(f32::BIT_COUNT).map(f32::from_bits)
 	}
 
 	#[inline]
-	pub unsafe fn read_string_unchecked(&mut self, bytes: usize) -> Option<String> {
-		self.read_bytes(bytes)
-			.map(|bytes| String::from_utf8_unchecked(bytes))
+	fn read_double(&mut self) -> Result<f64, ReadError> {
+		self.read_uint(f64::BIT_COUNT).map(f64::from_bits)
 	}
 
-	#[inline]
-	pub fn read_string(&mut self, bytes: usize) -> Option<Result<String, FromUtf8Error>> {
-		self.read_bytes(bytes).map(|bytes| String::from_utf8(bytes))
+	shared_reads!(, read_varint);
+
+	fn read_string(&mut self, bytes: usize) -> Result<String, ReadError> {
+		self.atomically(|reader| {
+			let offset = reader.bits_read();
+			let bytes = reader.read_bytes(bytes)?;
+			String::from_utf8(bytes).map_err(|error| ReadError::InvalidUtf8 {
+				offset,
+				error: error.utf8_error(),
+			})
+		})
 	}
 
-	#[inline]
-	pub fn read_string_lossy(&mut self, bytes: usize) -> Option<String> {
-		self.read_bytes(bytes)
-			.map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+	fn read_nul_string(&mut self) -> Result<String, ReadError> {
+		self.atomically(|reader| {
+			let offset = reader.bits_read();
+			let string = read_nul_terminated(reader)?;
+			String::from_utf8(string).map_err(|error| ReadError::InvalidUtf8 {
+				offset,
+				error: error.utf8_error(),
+			})
+		})
+	}
+
+	/// # Safety
+	///
+	/// The bytes read must be valid UTF-8.
+	unsafe fn read_nul_string_unchecked(&mut self) -> Result<String, ReadError> {
+		read_nul_terminated(self).map(|string| String::from_utf8_unchecked(string))
 	}
 
-	pub fn read_nul_string(&mut self) -> Option<Result<String, FromUtf8Error>> {
+	fn read_nul_string_lossy(&mut self) -> Result<String, ReadError> {
+		read_nul_terminated(self).map(|string| String::from_utf8_lossy(&string).into_owned())
+	}
+}
+
+/// Reads a varint a byte at a time, for the [`BitRead`] defaults.
+fn read_varint<R: BitRead + ?Sized>(reader: &mut R, max_bytes: usize) -> Result<u64, ReadError> {
+	let offset = reader.bits_read();
+	reader.atomically(|reader| {
+		let mut varint = 0;
+		for i in 0..max_bytes {
+			let byte = reader.read_byte()?;
+			varint |= ((byte & 0x7F) as u64) << (7 * i);
+			if byte & 0x80 == 0 {
+				return Ok(varint);
+			}
+		}
+		Err(ReadError::LengthLimitExceeded {
+			offset,
+			limit: max_bytes,
+		})
+	})
+}
+
+/// Reads the bytes up to the next nul terminator, and the terminator, a byte at a time for the [`BitRead`] defaults.
+fn read_nul_terminated<R: BitRead + ?Sized>(reader: &mut R) -> Result<Vec<u8>, ReadError> {
+	reader.atomically(|reader| {
 		let mut string = Vec::new();
-		while self.cursor < self.bitvec.len() {
-			let byte: u8 = self.bitvec[self.cursor..self.cursor + 8].load_bits();
-			self.advance(8);
-			if byte == 0 {
-				break;
-			} else {
-				string.push(byte);
+		loop {
+			match reader.read_byte()? {
+				0 => return Ok(string),
+				byte => string.push(byte),
 			}
 		}
-		Some(String::from_utf8(string))
-	}
+	})
+}
+
+/// Implements the [`BitRead`] methods that every reader in this crate has its own version of, by calling it. With no
+/// arguments, forwards all of them.
+macro_rules! forward_bit_read {
+	() => {
+		#[inline]
+		fn bits_read(&self) -> usize {
+			Self::bits_read(self)
+		}
+
+		#[inline]
+		fn read_uint<N>(&mut self, bits: usize) -> Result<N, ReadError>
+		where
+			N: BitMemory + IsNumber + IsUnsigned,
+		{
+			Self::read_uint(self, bits)
+		}
+
+		#[inline]
+		fn read_int<N>(&mut self, bits: usize) -> Result<N, ReadError>
+		where
+			N: FromBitMemory + IsNumber + IsSigned,
+		{
+			Self::read_int(self, bits)
+		}
+
+		#[inline]
+		fn atomically<T>(
+			&mut self,
+			read: impl FnOnce(&mut Self) -> Result<T, ReadError>,
+		) -> Result<T, ReadError> {
+			Self::atomically(self, read)
+		}
+
+		forward_bit_read! {
+			fn read_bit(&mut self) -> bool;
+			fn read_byte(&mut self) -> u8;
+			fn read_bytes(&mut self, len: usize) -> Vec<u8>;
+			fn read_uints_into(&mut self, out: &mut [u32], bits: usize) -> ();
+			fn read_float(&mut self) -> f32;
+			#[cfg(target_pointer_width = "64")]
+			fn read_double(&mut self) -> f64;
+			fn read_varint32(&mut self) -> u32;
+			fn read_varint64(&mut self) -> u64;
+			fn read_signed_varint32(&mut self) -> i32;
+			fn read_signed_varint64(&mut self) -> i64;
+			fn read_ubitvar(&mut self) -> u32;
+			fn read_field_index(&mut self, last_index: i32, new_way: bool) -> Option<i32>;
+			fn read_bit_coord(&mut self) -> f32;
+			fn read_bit_coord_mp(&mut self, integral: bool, low_precision: bool) -> f32;
+			fn read_bit_normal(&mut self) -> f32;
+			fn read_bit_angle(&mut self, bits: usize) -> f32;
+			fn read_bit_vec3_coord(&mut self) -> [f32; 3];
+			fn read_bit_vec3_normal(&mut self) -> [f32; 3];
+			fn read_string(&mut self, bytes: usize) -> String;
+			fn read_string_lossy(&mut self, bytes: usize) -> String;
+			fn read_nul_string(&mut self) -> String;
+			fn read_nul_string_lossy(&mut self) -> String;
+		}
+	};
+	( $( $(#[$attr:meta])* fn $name:ident(&mut self $(, $arg:ident: $ty:ty)*) -> $ret:ty; )* ) => {$(
+		$(#[$attr])*
+		#[inline]
+		fn $name(&mut self $(, $arg: $ty)*) -> Result<$ret, ReadError> {
+			Self::$name(self $(, $arg)*)
+		}
+	)*};
+}
+pub(crate) use forward_bit_read;
+
+/// Implements the unsafe [`BitRead`] reads for a reader that has its own versions of them.
+macro_rules! forward_bit_read_unchecked {
+	() => {
+		#[inline]
+		unsafe fn read_string_unchecked(&mut self, bytes: usize) -> Result<String, ReadError> {
+			Self::read_string_unchecked(self, bytes)
+		}
+
+		#[inline]
+		unsafe fn read_nul_string_unchecked(&mut self) -> Result<String, ReadError> {
+			Self::read_nul_string_unchecked(self)
+		}
+	};
+}
+pub(crate) use forward_bit_read_unchecked;
+
+/// The read API and [`io::Read`] impl shared by [`BitVecReader`] and [`BitSliceReader`], which read from the bits
+/// in `$field`.
+macro_rules! impl_reader {
+	( impl<$($lt:lifetime,)? O> $reader:ty, $lsb:ty, $msb:ty, $field:ident ) => {
+		impl<$($lt,)? O: BitOrder> $reader
+		where
+			BitSlice<O, u8>: BitField + LoadBits<O>,
+		{
+			#[inline]
+			pub fn bits_len(&self) -> usize {
+				self.$field.len()
+			}
+
+			/// Returns the number of bytes being read from, rounded up to the byte.
+			#[inline]
+			pub fn bytes_len(&self) -> usize {
+				(self.bits_len() + 7) / 8
+			}
+
+			#[inline]
+			pub fn bits_left(&self) -> usize {
+				self.$field.len() - self.cursor
+			}
+
+			#[inline]
+			pub fn bytes_left(&self) -> usize {
+				(self.bits_left() + 7) / 8
+			}
 
-	pub unsafe fn read_nul_string_unchecked(&mut self) -> Option<String> {
-		let mut string = String::new();
-		while self.cursor < self.bitvec.len() {
-			let byte: u8 = self.bitvec[self.cursor..self.cursor + 8].load_bits();
-			self.advance(8);
-			if byte == 0 {
-				break;
-			} else {
-				string.push(byte as char);
+			#[inline]
+			pub fn bits_read(&self) -> usize {
+				self.cursor
+			}
+
+			#[inline]
+			pub fn bytes_read(&self) -> usize {
+				(self.bits_read() + 7) / 8
+			}
+
+			#[inline]
+			pub fn advance(&mut self, n: usize) {
+				self.cursor += n;
+			}
+
+			#[inline]
+			pub fn rewind(&mut self, n: usize) {
+				self.cursor -= n;
+			}
+
+			#[inline]
+			fn check_range(&self, max: usize) -> Result<Range<usize>, ReadError> {
+				match self.cursor.checked_add(max) {
+					Some(end) if end <= self.$field.len() => Ok(self.cursor..end),
+					_ => Err(ReadError::OutOfBounds {
+						offset: self.cursor,
+						requested: max,
+						available: self.bits_left(),
+					}),
+				}
+			}
+
+			#[inline]
+			fn check_width(&self, bits: usize, max: usize) -> Result<(), ReadError> {
+				if bits == 0 || bits > max {
+					Err(ReadError::OutOfRange {
+						offset: self.cursor,
+						bits,
+						max,
+					})
+				} else {
+					Ok(())
+				}
+			}
+
+			#[inline]
+			fn read_bits(&self, max: usize) -> Result<&BitSlice<O, u8>, ReadError>
+			where
+				BitSlice<O, u8>: BitField + LoadBits<O>,
+			{
+				Ok(&self.$field[self.check_range(max)?])
+			}
+
+			#[inline]
+			pub fn read_bit(&mut self) -> Result<bool, ReadError> {
+				let bit = self.read_bits(1)?[0];
+				self.advance(1);
+				Ok(bit)
+			}
+
+			#[inline]
+			pub fn read_byte(&mut self) -> Result<u8, ReadError> {
+				let byte = self.read_bits(8)?.load_bits();
+				self.advance(8);
+				Ok(byte)
+			}
+
+			pub fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>, ReadError> {
+				let range = self.check_range(byte_bits(len))?;
+				let bytes = self.load_bytes(range);
+				self.advance(len * 8);
+				Ok(bytes)
+			}
+
+			#[inline]
+			fn load_bytes(&self, range: Range<usize>) -> Vec<u8> {
+				if let Some(bytes) = aligned_bytes(&self.$field[range.clone()]) {
+					return bytes.to_vec();
+				}
+				let mut bytes = Vec::with_capacity(range.len() / 8);
+				for i in range.step_by(8) {
+					let byte: u8 = self.$field[i..i + 8].load_bits();
+					bytes.push(byte);
+				}
+				bytes
+			}
+
+			/// Finds the nul terminator ahead of the cursor and returns the bytes before it, without advancing.
+			fn load_nul_string(&self) -> Result<Vec<u8>, ReadError> {
+				// On a byte boundary, search the underlying bytes directly. A partial byte at the end can't hold the
+				// terminator.
+				if let Domain::Region { head: None, body, .. } = self.$field[self.cursor..].domain() {
+					return match body.iter().position(|&byte| byte == 0) {
+						Some(len) => Ok(body[..len].to_vec()),
+						None => Err(ReadError::OutOfBounds {
+							offset: self.cursor,
+							requested: (body.len() + 1) * 8,
+							available: self.bits_left(),
+						}),
+					};
+				}
+				let mut string = Vec::new();
+				let mut i = self.cursor;
+				while i + 8 <= self.$field.len() {
+					let byte: u8 = self.$field[i..i + 8].load_bits();
+					if byte == 0 {
+						return Ok(string);
+					}
+					string.push(byte);
+					i += 8;
+				}
+				Err(ReadError::OutOfBounds {
+					offset: self.cursor,
+					requested: (string.len() + 1) * 8,
+					available: self.bits_left(),
+				})
+			}
+
+			#[inline]
+			pub fn read_uint<N>(&mut self, bits: usize) -> Result<N, ReadError>
+			where
+				N: BitMemory + IsNumber + IsUnsigned,
+			{
+				self.check_width(bits, N::BITS as usize)?;
+				let uint: N = self.read_bits(bits)?.load_bits();
+				self.advance(bits);
+				Ok(uint)
+			}
+
+			#[inline]
+			pub fn read_int<N>(&mut self, bits: usize) -> Result<N, ReadError>
+			where
+				N: FromBitMemory + IsNumber + IsSigned,
+			{
+				self.check_width(bits, N::BIT_COUNT)?;
+				let int: <N as FromBitMemory>::Unsigned = self.read_bits(bits)?.load_bits();
+				self.advance(bits);
+				Ok(N::from_bitmemory(int, bits))
+			}
+
+			/// Reads `out.len()` consecutive `bits`-bit uints into `out`, for `bits` in `1..=32`.
+			///
+			/// This is much faster than calling [`read_uint`](Self::read_uint) for each of them, as several uints are
+			/// loaded at once. If there aren't enough bits left, `out` is left untouched.
+			pub fn read_uints_into(&mut self, out: &mut [u32], bits: usize) -> Result<(), ReadError> {
+				self.check_width(bits, 32)?;
+				let range = self.check_range(out.len() * bits)?;
+				self.$field[range].load_uints(out, bits);
+				self.advance(out.len() * bits);
+				Ok(())
+			}
+
+			#[inline]
+			pub fn read_float(&mut self) -> Result<f32, ReadError> {
+				let float: <f32 as FromBitMemory>::Unsigned = self.read_bits(f32::BIT_COUNT)?.load_bits();
+				self.advance(f32::BIT_COUNT);
+				Ok(f32::from_bitmemory(float, f32::BIT_COUNT))
+			}
+
+			#[cfg(target_pointer_width = "64")]
+			#[inline]
+			pub fn read_double(&mut self) -> Result<f64, ReadError> {
+				let float: <f64 as FromBitMemory>::Unsigned = self.read_bits(f64::BIT_COUNT)?.load_bits();
+				self.advance(f64::BIT_COUNT);
+				Ok(f64::from_bitmemory(float, f64::BIT_COUNT))
+			}
+
+			shared_reads!(pub, Self::read_varint);
+
+			fn read_varint(&mut self, max_bytes: usize) -> Result<u64, ReadError> {
+				let mut varint = 0;
+				for i in 0..max_bytes {
+					let range = self.check_range((i + 1) * 8)?;
+					let byte: u8 = self.$field[range.end - 8..range.end].load_bits();
+					varint |= ((byte & 0x7F) as u64) << (7 * i);
+					if byte & 0x80 == 0 {
+						self.advance((i + 1) * 8);
+						return Ok(varint);
+					}
+				}
+				Err(ReadError::LengthLimitExceeded {
+					offset: self.cursor,
+					limit: max_bytes,
+				})
+			}
+
+			#[inline]
+			pub fn read_string(&mut self, bytes: usize) -> Result<String, ReadError> {
+				let range = self.check_range(byte_bits(bytes))?;
+				let string = self.utf8(self.load_bytes(range))?;
+				self.advance(bytes * 8);
+				Ok(string)
+			}
+
+			pub fn read_nul_string(&mut self) -> Result<String, ReadError> {
+				let string = self.load_nul_string()?;
+				let len = string.len();
+				let string = self.utf8(string)?;
+				self.advance((len + 1) * 8);
+				Ok(string)
+			}
+
+			/// # Safety
+			///
+			/// The bytes read must be valid UTF-8.
+			pub unsafe fn read_nul_string_unchecked(&mut self) -> Result<String, ReadError> {
+				let string = self.load_nul_string()?;
+				self.advance((string.len() + 1) * 8);
+				Ok(String::from_utf8_unchecked(string))
+			}
+
+			pub fn read_nul_string_lossy(&mut self) -> Result<String, ReadError> {
+				let string = self.load_nul_string()?;
+				self.advance((string.len() + 1) * 8);
+				Ok(String::from_utf8_lossy(&string).into_owned())
+			}
+
+			/// Runs `read` and moves the cursor back to where it was, whether it succeeded or not.
+			#[inline]
+			pub fn peek_with<T>(
+				&mut self,
+				read: impl FnOnce(&mut Self) -> Result<T, ReadError>,
+			) -> Result<T, ReadError> {
+				let cursor = self.cursor;
+				let result = read(self);
+				self.cursor = cursor;
+				result
+			}
+
+			#[inline]
+			pub fn peek_bit(&mut self) -> Result<bool, ReadError> {
+				self.peek_with(Self::read_bit)
+			}
+
+			#[inline]
+			pub fn peek_bytes(&mut self, len: usize) -> Result<Vec<u8>, ReadError> {
+				self.peek_with(|reader| reader.read_bytes(len))
+			}
+
+			#[inline]
+			pub fn peek_uint<N>(&mut self, bits: usize) -> Result<N, ReadError>
+			where
+				N: BitMemory + IsNumber + IsUnsigned,
+			{
+				self.peek_with(|reader| reader.read_uint(bits))
+			}
+
+			#[inline]
+			pub fn peek_int<N>(&mut self, bits: usize) -> Result<N, ReadError>
+			where
+				N: FromBitMemory + IsNumber + IsSigned,
+			{
+				self.peek_with(|reader| reader.read_int(bits))
+			}
+
+			/// Runs a read made up of several smaller reads, rewinding the cursor if any of them fail.
+			#[inline]
+			pub(crate) fn atomically<T>(
+				&mut self,
+				read: impl FnOnce(&mut Self) -> Result<T, ReadError>,
+			) -> Result<T, ReadError> {
+				let cursor = self.cursor;
+				let result = read(self);
+				if result.is_err() {
+					self.cursor = cursor;
+				}
+				result
+			}
+
+			#[inline]
+			fn utf8(&self, bytes: Vec<u8>) -> Result<String, ReadError> {
+				String::from_utf8(bytes).map_err(|error| ReadError::InvalidUtf8 {
+					offset: self.cursor,
+					error: error.utf8_error(),
+				})
 			}
 		}
-		Some(string)
-	}
 
-	pub fn read_nul_string_lossy(&mut self) -> Option<String> {
-		let mut string = Vec::new();
-		while self.cursor < self.bitvec.len() {
-			let byte: u8 = self.bitvec[self.cursor..self.cursor + 8].load_bits();
-			self.advance(8);
-			if byte == 0 {
-				break;
-			} else {
-				string.push(byte);
+		impl<$($lt,)? O: BitOrder> BitRead for $reader
+		where
+			BitSlice<O, u8>: BitField + LoadBits<O>,
+		{
+			#[inline]
+			fn bits_left(&self) -> Option<usize> {
+				Some(Self::bits_left(self))
 			}
+
+			forward_bit_read!();
+			forward_bit_read_unchecked!();
+		}
+
+	impl<$($lt)?> $msb {
+		#[cfg(target_pointer_width = "32")]
+		#[inline]
+		pub fn read_double(&mut self) -> Result<f64, ReadError> {
+			self.check_range(64)?;
+			let double1 = self.read_uint::<u32>(32)? as u64;
+			let double2 = self.read_uint::<u32>(32)? as u64;
+			let double = (double1 << 32) + double2;
+			Ok(f64::from_bits(double))
+		}
+	}
+	impl<$($lt)?> $lsb {
+		#[cfg(target_pointer_width = "32")]
+		#[inline]
+		pub fn read_double(&mut self) -> Result<f64, ReadError> {
+			self.check_range(64)?;
+			let double1 = self.read_uint::<u32>(32)? as u64;
+			let double2 = self.read_uint::<u32>(32)? as u64;
+			let double = (double2 << 32) + double1;
+			Ok(f64::from_bits(double))
 		}
-		Some(String::from_utf8_lossy(&string).into_owned())
 	}
+
+		/// Reads bytes at the cursor, even if it isn't byte-aligned. Stops before any trailing bits that don't make
+		/// up a whole byte.
+		impl<$($lt,)? O: BitOrder> io::Read for $reader
+		where
+			BitSlice<O, u8>: BitField + LoadBits<O>,
+		{
+			fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+				let len = buf.len().min(self.bits_left() / 8);
+				for (i, byte) in buf[..len].iter_mut().enumerate() {
+					let start = self.cursor + i * 8;
+					*byte = self.$field[start..start + 8].load_bits();
+				}
+				self.advance(len * 8);
+				Ok(len)
+			}
+		}
+	};
 }
+impl_reader!(impl<O> BitVecReader<O>, BitVecReader<Lsb0>, BitVecReader<Msb0>, bitvec);
+impl_reader!(impl<'a, O> BitSliceReader<'a, O>, BitSliceReader<'a, Lsb0>, BitSliceReader<'a, Msb0>, bitslice);
 
-impl BitVecReader<Msb0> {
-	#[cfg(target_pointer_width = "32")]
-	#[inline]
-	pub fn read_double(&mut self) -> Option<f64> {
-		let double1 = self.read_uint::<u32>(32)? as u64;
-		let double2 = self.read_uint::<u32>(32)? as u64;
-		let double = (double1 << 32) + double2;
-		let double: f64 = unsafe { std::mem::transmute(double) };
-		Some(double)
-	}
+/// Returns the number of bits in `bytes` bytes, or `usize::MAX` if that overflows, which no range check lets through.
+#[inline]
+pub(crate) fn byte_bits(bytes: usize) -> usize {
+	bytes.saturating_mul(8)
 }
-impl BitVecReader<Lsb0> {
-	#[cfg(target_pointer_width = "32")]
-	#[inline]
-	pub fn read_double(&mut self) -> Option<f64> {
-		let double1 = self.read_uint::<u32>(32)? as u64;
-		let double2 = self.read_uint::<u32>(32)? as u64;
-		let double = (double2 << 32) + double1;
-		let double: f64 = unsafe { std::mem::transmute(double) };
-		Some(double)
-	}
+
+#[inline]
+pub(crate) fn zigzag_decode32(n: u32) -> i32 {
+	(n >> 1) as i32 ^ -((n & 1) as i32)
+}
+
+#[inline]
+pub(crate) fn zigzag_decode64(n: u64) -> i64 {
+	(n >> 1) as i64 ^ -((n & 1) as i64)
 }
 
 impl<O: BitOrder> From<Vec<u8>> for BitVecReader<O>
@@ -265,12 +1067,24 @@ where
 		BitVecReader::from_bytes(bytes)
 	}
 }
+impl<'a, O: BitOrder> From<&'a [u8]> for BitSliceReader<'a, O>
+where
+	BitSlice<O, u8>: BitField + LoadBits<O>,
+{
+	fn from(bytes: &'a [u8]) -> Self {
+		BitSliceReader::from_bytes(bytes)
+	}
+}
 
 pub trait LoadBits<O: BitOrder>
 where
 	BitSlice<O, u8>: BitField,
 {
 	fn load_bits<M: BitMemory>(&self) -> M;
+
+	/// Loads `out.len()` consecutive `bits`-bit uints, for `bits` in `1..=32`. `self` must be `out.len() * bits`
+	/// bits long.
+	fn load_uints(&self, out: &mut [u32], bits: usize);
 }
 impl<O: BitOrder> LoadBits<O> for BitSlice<Lsb0, u8>
 where
@@ -279,6 +1093,10 @@ where
 	fn load_bits<M: BitMemory>(&self) -> M {
 		self.load_le()
 	}
+
+	fn load_uints(&self, out: &mut [u32], bits: usize) {
+		unpack_uints::<Lsb0>(self, out, bits)
+	}
 }
 impl<O: BitOrder> LoadBits<O> for BitSlice<Msb0, u8>
 where
@@ -287,6 +1105,23 @@ where
 	fn load_bits<M: BitMemory>(&self) -> M {
 		self.load_be()
 	}
+
+	fn load_uints(&self, out: &mut [u32], bits: usize) {
+		unpack_uints::<Msb0>(self, out, bits)
+	}
+}
+
+/// Splits `slice` into `bits`-bit uints by reading the bytes under it with a [`CachedBitReader`], which loads them
+/// 64 bits at a time.
+#[inline]
+fn unpack_uints<O: CacheOrder>(slice: &BitSlice<O, u8>, out: &mut [u32], bits: usize) {
+	if out.is_empty() {
+		return;
+	}
+	let (_, head) = slice.as_bitptr().raw_parts();
+	let mut reader = CachedBitReader::<O>::new(slice.as_raw_slice());
+	reader.seek(head.into_inner() as usize);
+	reader.take_uints(out, bits);
 }
 
 pub trait FromBitMemory: BitCount {
@@ -298,12 +1133,12 @@ macro_rules! impl_into_bitmemory {
 		impl FromBitMemory for $from {
 			type Unsigned = $to;
 			fn from_bitmemory(u: Self::Unsigned, bits: usize) -> Self {
-				if bits == Self::BIT_COUNT {
-					unsafe { std::mem::transmute(u) }
+				if bits == Self::BIT_COUNT || u >> (bits - 1) == 0 {
+					u as $from
 				} else {
 					let remainder = Self::BIT_COUNT - bits;
 					let mask = (<$to>::pow(2, remainder as _) - 1) as $to << (Self::BIT_COUNT - remainder);
-					unsafe { std::mem::transmute(u + mask) }
+					(u + mask) as $from
 				}
 			}
 		}
@@ -314,7 +1149,7 @@ macro_rules! impl_into_bitmemory_float {
 		impl FromBitMemory for $from {
 			type Unsigned = <$from as IsFloat>::Raw;
 			fn from_bitmemory(u: Self::Unsigned, _bits: usize) -> Self {
-				unsafe { std::mem::transmute(u) }
+				<$from>::from_bits(u)
 			}
 		}
 	};
@@ -323,6 +1158,7 @@ impl_into_bitmemory!(i8, u8);
 impl_into_bitmemory!(i16, u16);
 impl_into_bitmemory!(i32, u32);
 impl_into_bitmemory!(i64, u64);
+impl_into_bitmemory!(i128, u128);
 impl_into_bitmemory!(isize, usize);
 impl_into_bitmemory_float!(f32);
 impl_into_bitmemory_float!(f64);
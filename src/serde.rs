@@ -0,0 +1,805 @@
+//! Serde serialization into a [`BitVecWriter`] and deserialization from a [`BitVecReader`].
+//!
+//! The format is compact and not self-describing:
+//!
+//! * `bool` is a single bit, and an `Option` is a bit followed by the value if it is `Some`.
+//! * Integers are written at their full width, and floats as their IEEE 754 bits.
+//! * `char` is a varint of its code point.
+//! * Strings, byte arrays, sequences and maps are prefixed with their length as a varint.
+//! * Structs and tuples are their fields in order, with no names or lengths.
+//! * Enums are the variant index as a varint, followed by the variant's fields.
+//!
+//! The width of an integer field can be chosen with `#[serde(with = "bitvecbuf::serde::Bits::<12>")]`,
+//! and an integer field can be written as a varint (zigzag-encoded if signed) with
+//! `#[serde(with = "bitvecbuf::serde::varint")]`. Both also work on an `Option` of an integer. Other
+//! serializers see these fields as plain integers.
+
+use std::{convert::TryFrom, fmt, marker::PhantomData};
+
+use ::serde::{
+	de::{self, DeserializeSeed, IntoDeserializer, Visitor},
+	ser::{self, Serialize},
+	Deserialize, Deserializer as _,
+};
+use bitvec::{field::BitField, order::BitOrder, slice::BitSlice};
+
+use crate::{read::LoadBits, write::StoreBits, BitVecReader, BitVecWriter, ReadError};
+
+const BITS_NAME: &str = "\0bitvecbuf::serde::Bits";
+const VARINT_NAME: &str = "\0bitvecbuf::serde::varint";
+
+macro_rules! bits_names {
+	( $($n:literal)* ) => {
+		[$(concat!("\0bitvecbuf::serde::Bits", $n)),*]
+	};
+}
+const BITS_NAMES: [&str; 65] = bits_names!(
+	0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+	33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63 64
+);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IntEncoding {
+	Bits(usize),
+	Varint,
+}
+impl IntEncoding {
+	fn from_name(name: &str) -> Option<IntEncoding> {
+		if name == VARINT_NAME {
+			Some(IntEncoding::Varint)
+		} else if let Some(bits) = name.strip_prefix(BITS_NAME) {
+			bits.parse().ok().map(IntEncoding::Bits)
+		} else {
+			None
+		}
+	}
+}
+
+/// Encodes an integer field with `N` bits, for use with `#[serde(with = "bitvecbuf::serde::Bits::<N>")]`.
+///
+/// `N` must be in `1..=64`, which is checked at compile time.
+pub struct Bits<const N: usize>;
+impl<const N: usize> Bits<N> {
+	const NAME: &'static str = {
+		assert!(N > 0 && N <= 64, "`Bits::<N>` needs `N` in `1..=64`");
+		BITS_NAMES[N]
+	};
+
+	pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		T: ?Sized + Serialize,
+		S: ser::Serializer,
+	{
+		serializer.serialize_newtype_struct(Self::NAME, value)
+	}
+
+	pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+	where
+		T: Deserialize<'de>,
+		D: de::Deserializer<'de>,
+	{
+		deserializer.deserialize_newtype_struct(Self::NAME, NewtypeVisitor(PhantomData))
+	}
+}
+
+/// Encodes an integer field as a varint, for use with `#[serde(with = "bitvecbuf::serde::varint")]`.
+pub mod varint {
+	use super::*;
+
+	pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		T: ?Sized + Serialize,
+		S: ser::Serializer,
+	{
+		serializer.serialize_newtype_struct(VARINT_NAME, value)
+	}
+
+	pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+	where
+		T: Deserialize<'de>,
+		D: de::Deserializer<'de>,
+	{
+		deserializer.deserialize_newtype_struct(VARINT_NAME, NewtypeVisitor(PhantomData))
+	}
+}
+
+struct NewtypeVisitor<T>(PhantomData<T>);
+impl<'de, T: Deserialize<'de>> Visitor<'de> for NewtypeVisitor<T> {
+	type Value = T;
+
+	fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("an integer")
+	}
+
+	fn visit_newtype_struct<D: de::Deserializer<'de>>(
+		self,
+		deserializer: D,
+	) -> Result<T, D::Error> {
+		T::deserialize(deserializer)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+	Read(ReadError),
+	/// The writer overflowed its [`max_bits`](BitVecWriter::max_bits).
+	Overflow,
+	/// A sequence or map was serialized without knowing its length up front.
+	UnknownLength,
+	/// The type can't be deserialized from a format that isn't self-describing.
+	NotSelfDescribing,
+	Message(String),
+}
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Error::Read(error) => error.fmt(f),
+			Error::Overflow => f.write_str("the writer overflowed"),
+			Error::UnknownLength => f.write_str("sequences and maps must know their length"),
+			Error::NotSelfDescribing => f.write_str("the format is not self-describing"),
+			Error::Message(message) => f.write_str(message),
+		}
+	}
+}
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Error::Read(error) => Some(error),
+			_ => None,
+		}
+	}
+}
+impl From<ReadError> for Error {
+	fn from(error: ReadError) -> Self {
+		Error::Read(error)
+	}
+}
+impl ser::Error for Error {
+	fn custom<T: fmt::Display>(msg: T) -> Self {
+		Error::Message(msg.to_string())
+	}
+}
+impl de::Error for Error {
+	fn custom<T: fmt::Display>(msg: T) -> Self {
+		Error::Message(msg.to_string())
+	}
+}
+
+/// Serializes `value` into `writer`.
+pub fn to_writer<O, T>(writer: &mut BitVecWriter<O>, value: &T) -> Result<(), Error>
+where
+	O: BitOrder,
+	BitSlice<O, u8>: BitField + StoreBits<O>,
+	T: ?Sized + Serialize,
+{
+	value.serialize(&mut Serializer::new(writer))?;
+	if writer.is_overflowed() {
+		Err(Error::Overflow)
+	} else {
+		Ok(())
+	}
+}
+
+/// Deserializes a `T` from `reader`. If this fails, the cursor is left where it was.
+pub fn from_reader<'de, O, T>(reader: &mut BitVecReader<O>) -> Result<T, Error>
+where
+	O: BitOrder,
+	BitSlice<O, u8>: BitField + LoadBits<O>,
+	T: Deserialize<'de>,
+{
+	let cursor = reader.cursor;
+	let value = T::deserialize(&mut Deserializer::new(reader));
+	if value.is_err() {
+		reader.cursor = cursor;
+	}
+	value
+}
+
+pub struct Serializer<'w, O: BitOrder> {
+	writer: &'w mut BitVecWriter<O>,
+	encoding: Option<IntEncoding>,
+}
+impl<'w, O: BitOrder> Serializer<'w, O>
+where
+	BitSlice<O, u8>: BitField + StoreBits<O>,
+{
+	#[inline]
+	pub fn new(writer: &'w mut BitVecWriter<O>) -> Self {
+		Serializer {
+			writer,
+			encoding: None,
+		}
+	}
+
+	#[inline]
+	fn write_len(&mut self, len: Option<usize>) -> Result<(), Error> {
+		self.writer
+			.write_varint64(len.ok_or(Error::UnknownLength)? as u64);
+		Ok(())
+	}
+
+	fn write_uint(&mut self, uint: u64, bits: usize) {
+		match self.encoding.take() {
+			None => self.writer.write_uint(uint, bits),
+			Some(IntEncoding::Bits(bits)) => self.writer.write_uint(uint, bits),
+			Some(IntEncoding::Varint) => self.writer.write_varint64(uint),
+		}
+	}
+
+	fn write_int(&mut self, int: i64, bits: usize) {
+		match self.encoding.take() {
+			None => self.writer.write_int(int, bits),
+			Some(IntEncoding::Bits(bits)) => self.writer.write_int(int, bits),
+			Some(IntEncoding::Varint) => self.writer.write_signed_varint64(int),
+		}
+	}
+}
+
+impl<'a, 'w, O: BitOrder> ser::Serializer for &'a mut Serializer<'w, O>
+where
+	BitSlice<O, u8>: BitField + StoreBits<O>,
+{
+	type Ok = ();
+	type Error = Error;
+
+	type SerializeSeq = Self;
+	type SerializeTuple = Self;
+	type SerializeTupleStruct = Self;
+	type SerializeTupleVariant = Self;
+	type SerializeMap = Self;
+	type SerializeStruct = Self;
+	type SerializeStructVariant = Self;
+
+	fn serialize_bool(self, v: bool) -> Result<(), Error> {
+		self.writer.write_bit(v);
+		Ok(())
+	}
+
+	fn serialize_i8(self, v: i8) -> Result<(), Error> {
+		self.write_int(v as i64, 8);
+		Ok(())
+	}
+
+	fn serialize_i16(self, v: i16) -> Result<(), Error> {
+		self.write_int(v as i64, 16);
+		Ok(())
+	}
+
+	fn serialize_i32(self, v: i32) -> Result<(), Error> {
+		self.write_int(v as i64, 32);
+		Ok(())
+	}
+
+	fn serialize_i64(self, v: i64) -> Result<(), Error> {
+		self.write_int(v, 64);
+		Ok(())
+	}
+
+	fn serialize_u8(self, v: u8) -> Result<(), Error> {
+		self.write_uint(v as u64, 8);
+		Ok(())
+	}
+
+	fn serialize_u16(self, v: u16) -> Result<(), Error> {
+		self.write_uint(v as u64, 16);
+		Ok(())
+	}
+
+	fn serialize_u32(self, v: u32) -> Result<(), Error> {
+		self.write_uint(v as u64, 32);
+		Ok(())
+	}
+
+	fn serialize_u64(self, v: u64) -> Result<(), Error> {
+		self.write_uint(v, 64);
+		Ok(())
+	}
+
+	fn serialize_f32(self, v: f32) -> Result<(), Error> {
+		self.writer.write_float(v);
+		Ok(())
+	}
+
+	fn serialize_f64(self, v: f64) -> Result<(), Error> {
+		self.writer.write_double(v);
+		Ok(())
+	}
+
+	fn serialize_char(self, v: char) -> Result<(), Error> {
+		self.writer.write_varint32(v as u32);
+		Ok(())
+	}
+
+	fn serialize_str(self, v: &str) -> Result<(), Error> {
+		self.write_len(Some(v.len()))?;
+		self.writer.write_string(v);
+		Ok(())
+	}
+
+	fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+		self.write_len(Some(v.len()))?;
+		self.writer.write_bytes(v);
+		Ok(())
+	}
+
+	fn serialize_none(self) -> Result<(), Error> {
+		self.writer.write_bit(false);
+		Ok(())
+	}
+
+	fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), Error> {
+		self.writer.write_bit(true);
+		value.serialize(self)
+	}
+
+	fn serialize_unit(self) -> Result<(), Error> {
+		Ok(())
+	}
+
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+		Ok(())
+	}
+
+	fn serialize_unit_variant(
+		self,
+		_name: &'static str,
+		variant_index: u32,
+		_variant: &'static str,
+	) -> Result<(), Error> {
+		self.writer.write_varint32(variant_index);
+		Ok(())
+	}
+
+	fn serialize_newtype_struct<T: ?Sized + Serialize>(
+		self,
+		name: &'static str,
+		value: &T,
+	) -> Result<(), Error> {
+		match IntEncoding::from_name(name) {
+			Some(encoding) => {
+				self.encoding = Some(encoding);
+				let result = value.serialize(&mut *self);
+				self.encoding = None;
+				result
+			}
+			None => value.serialize(self),
+		}
+	}
+
+	fn serialize_newtype_variant<T: ?Sized + Serialize>(
+		self,
+		_name: &'static str,
+		variant_index: u32,
+		_variant: &'static str,
+		value: &T,
+	) -> Result<(), Error> {
+		self.writer.write_varint32(variant_index);
+		value.serialize(self)
+	}
+
+	fn serialize_seq(self, len: Option<usize>) -> Result<Self, Error> {
+		self.write_len(len)?;
+		Ok(self)
+	}
+
+	fn serialize_tuple(self, _len: usize) -> Result<Self, Error> {
+		Ok(self)
+	}
+
+	fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+		Ok(self)
+	}
+
+	fn serialize_tuple_variant(
+		self,
+		_name: &'static str,
+		variant_index: u32,
+		_variant: &'static str,
+		_len: usize,
+	) -> Result<Self, Error> {
+		self.writer.write_varint32(variant_index);
+		Ok(self)
+	}
+
+	fn serialize_map(self, len: Option<usize>) -> Result<Self, Error> {
+		self.write_len(len)?;
+		Ok(self)
+	}
+
+	fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+		Ok(self)
+	}
+
+	fn serialize_struct_variant(
+		self,
+		_name: &'static str,
+		variant_index: u32,
+		_variant: &'static str,
+		_len: usize,
+	) -> Result<Self, Error> {
+		self.writer.write_varint32(variant_index);
+		Ok(self)
+	}
+
+	fn is_human_readable(&self) -> bool {
+		false
+	}
+}
+
+macro_rules! impl_serialize_compound {
+	( $trait:ident, $fn:ident $(, $key:ident)? ) => {
+		impl<'a, 'w, O: BitOrder> ser::$trait for &'a mut Serializer<'w, O>
+		where
+			BitSlice<O, u8>: BitField + StoreBits<O>,
+		{
+			type Ok = ();
+			type Error = Error;
+
+			fn $fn<T: ?Sized + Serialize>(&mut self, $($key: &'static str,)? value: &T) -> Result<(), Error> {
+				value.serialize(&mut **self)
+			}
+
+			fn end(self) -> Result<(), Error> {
+				Ok(())
+			}
+		}
+	};
+}
+impl_serialize_compound!(SerializeSeq, serialize_element);
+impl_serialize_compound!(SerializeTuple, serialize_element);
+impl_serialize_compound!(SerializeTupleStruct, serialize_field);
+impl_serialize_compound!(SerializeTupleVariant, serialize_field);
+impl_serialize_compound!(SerializeStruct, serialize_field, _key);
+impl_serialize_compound!(SerializeStructVariant, serialize_field, _key);
+
+impl<'a, 'w, O: BitOrder> ser::SerializeMap for &'a mut Serializer<'w, O>
+where
+	BitSlice<O, u8>: BitField + StoreBits<O>,
+{
+	type Ok = ();
+	type Error = Error;
+
+	fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+		key.serialize(&mut **self)
+	}
+
+	fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+		value.serialize(&mut **self)
+	}
+
+	fn end(self) -> Result<(), Error> {
+		Ok(())
+	}
+}
+
+pub struct Deserializer<'r, O: BitOrder> {
+	reader: &'r mut BitVecReader<O>,
+	encoding: Option<IntEncoding>,
+}
+impl<'r, O: BitOrder> Deserializer<'r, O>
+where
+	BitSlice<O, u8>: BitField + LoadBits<O>,
+{
+	#[inline]
+	pub fn new(reader: &'r mut BitVecReader<O>) -> Self {
+		Deserializer {
+			reader,
+			encoding: None,
+		}
+	}
+
+	#[inline]
+	fn read_len(&mut self) -> Result<usize, Error> {
+		let offset = self.reader.cursor;
+		let len = self.reader.read_varint64()?;
+		usize::try_from(len)
+			.map_err(|_| Error::Read(ReadError::InvalidValue { offset, value: len }))
+	}
+
+	/// Reads the length of a string or byte buffer, which can't be more than the bytes left.
+	#[inline]
+	fn read_byte_len(&mut self) -> Result<usize, Error> {
+		let len = self.read_len()?;
+		let available = self.reader.bits_left();
+		if len > available / 8 {
+			return Err(Error::Read(ReadError::OutOfBounds {
+				offset: self.reader.cursor,
+				requested: len.saturating_mul(8),
+				available,
+			}));
+		}
+		Ok(len)
+	}
+
+	fn read_uint(&mut self, bits: usize) -> Result<u64, Error> {
+		Ok(match self.encoding.take() {
+			None => self.reader.read_uint(bits)?,
+			Some(IntEncoding::Bits(bits)) => self.reader.read_uint(bits)?,
+			Some(IntEncoding::Varint) => self.reader.read_varint64()?,
+		})
+	}
+
+	fn read_int(&mut self, bits: usize) -> Result<i64, Error> {
+		Ok(match self.encoding.take() {
+			None => self.reader.read_int(bits)?,
+			Some(IntEncoding::Bits(bits)) => self.reader.read_int(bits)?,
+			Some(IntEncoding::Varint) => self.reader.read_signed_varint64()?,
+		})
+	}
+}
+
+impl<'de, 'a, 'r, O: BitOrder> de::Deserializer<'de> for &'a mut Deserializer<'r, O>
+where
+	BitSlice<O, u8>: BitField + LoadBits<O>,
+{
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+		Err(Error::NotSelfDescribing)
+	}
+
+	fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_bool(self.reader.read_bit()?)
+	}
+
+	fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_i64(self.read_int(8)?)
+	}
+
+	fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_i64(self.read_int(16)?)
+	}
+
+	fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_i64(self.read_int(32)?)
+	}
+
+	fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_i64(self.read_int(64)?)
+	}
+
+	fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_u64(self.read_uint(8)?)
+	}
+
+	fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_u64(self.read_uint(16)?)
+	}
+
+	fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_u64(self.read_uint(32)?)
+	}
+
+	fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_u64(self.read_uint(64)?)
+	}
+
+	fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_f32(self.reader.read_float()?)
+	}
+
+	fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_f64(self.reader.read_double()?)
+	}
+
+	fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		let offset = self.reader.cursor;
+		let char = self.reader.read_varint32()?;
+		match char::from_u32(char) {
+			Some(char) => visitor.visit_char(char),
+			None => Err(Error::Read(ReadError::InvalidValue {
+				offset,
+				value: char as u64,
+			})),
+		}
+	}
+
+	fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.deserialize_string(visitor)
+	}
+
+	fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		let len = self.read_byte_len()?;
+		visitor.visit_string(self.reader.read_string(len)?)
+	}
+
+	fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		self.deserialize_byte_buf(visitor)
+	}
+
+	fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		let len = self.read_byte_len()?;
+		visitor.visit_byte_buf(self.reader.read_bytes(len)?)
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		if self.reader.read_bit()? {
+			visitor.visit_some(self)
+		} else {
+			self.encoding = None;
+			visitor.visit_none()
+		}
+	}
+
+	fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_unit()
+	}
+
+	fn deserialize_unit_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		visitor.visit_unit()
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		match IntEncoding::from_name(name) {
+			Some(encoding) => {
+				self.encoding = Some(encoding);
+				let result = visitor.visit_newtype_struct(&mut *self);
+				self.encoding = None;
+				result
+			}
+			None => visitor.visit_newtype_struct(self),
+		}
+	}
+
+	fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		let len = self.read_len()?;
+		visitor.visit_seq(Access {
+			deserializer: self,
+			len,
+		})
+	}
+
+	fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_seq(Access {
+			deserializer: self,
+			len,
+		})
+	}
+
+	fn deserialize_tuple_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		len: usize,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.deserialize_tuple(len, visitor)
+	}
+
+	fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		let len = self.read_len()?;
+		visitor.visit_map(Access {
+			deserializer: self,
+			len,
+		})
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.deserialize_tuple(fields.len(), visitor)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		visitor.visit_enum(self)
+	}
+
+	fn deserialize_identifier<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+		Err(Error::NotSelfDescribing)
+	}
+
+	fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+		Err(Error::NotSelfDescribing)
+	}
+
+	fn is_human_readable(&self) -> bool {
+		false
+	}
+}
+
+struct Access<'a, 'r, O: BitOrder> {
+	deserializer: &'a mut Deserializer<'r, O>,
+	len: usize,
+}
+impl<'de, 'a, 'r, O: BitOrder> de::SeqAccess<'de> for Access<'a, 'r, O>
+where
+	BitSlice<O, u8>: BitField + LoadBits<O>,
+{
+	type Error = Error;
+
+	fn next_element_seed<T: DeserializeSeed<'de>>(
+		&mut self,
+		seed: T,
+	) -> Result<Option<T::Value>, Error> {
+		if self.len == 0 {
+			return Ok(None);
+		}
+		self.len -= 1;
+		seed.deserialize(&mut *self.deserializer).map(Some)
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.len)
+	}
+}
+impl<'de, 'a, 'r, O: BitOrder> de::MapAccess<'de> for Access<'a, 'r, O>
+where
+	BitSlice<O, u8>: BitField + LoadBits<O>,
+{
+	type Error = Error;
+
+	fn next_key_seed<K: DeserializeSeed<'de>>(
+		&mut self,
+		seed: K,
+	) -> Result<Option<K::Value>, Error> {
+		if self.len == 0 {
+			return Ok(None);
+		}
+		self.len -= 1;
+		seed.deserialize(&mut *self.deserializer).map(Some)
+	}
+
+	fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+		seed.deserialize(&mut *self.deserializer)
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.len)
+	}
+}
+
+impl<'de, 'a, 'r, O: BitOrder> de::EnumAccess<'de> for &'a mut Deserializer<'r, O>
+where
+	BitSlice<O, u8>: BitField + LoadBits<O>,
+{
+	type Error = Error;
+	type Variant = Self;
+
+	fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+		let variant_index = self.reader.read_varint32()?;
+		let value =
+			seed.deserialize(IntoDeserializer::<Error>::into_deserializer(variant_index))?;
+		Ok((value, self))
+	}
+}
+impl<'de, 'a, 'r, O: BitOrder> de::VariantAccess<'de> for &'a mut Deserializer<'r, O>
+where
+	BitSlice<O, u8>: BitField + LoadBits<O>,
+{
+	type Error = Error;
+
+	fn unit_variant(self) -> Result<(), Error> {
+		Ok(())
+	}
+
+	fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+		seed.deserialize(self)
+	}
+
+	fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Error> {
+		self.deserialize_tuple(len, visitor)
+	}
+
+	fn struct_variant<V: Visitor<'de>>(
+		self,
+		fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		self.deserialize_tuple(fields.len(), visitor)
+	}
+}
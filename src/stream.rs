@@ -0,0 +1,534 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use bitvec::{
+	field::BitField,
+	macros::internal::funty::{IsNumber, IsSigned, IsUnsigned},
+	mem::BitMemory,
+	order::BitOrder,
+	slice::BitSlice,
+	vec::BitVec,
+};
+
+use crate::{
+	read::{forward_bit_read, FromBitMemory, LoadBits},
+	write::{forward_bit_write, IntoBitView, StoreBits},
+	BitRead, BitVecReader, BitVecWriter, BitWrite, ReadError,
+};
+
+/// The number of bytes a [`StreamBitReader`] reads from its source at a time, unless it needs more.
+const DEFAULT_STREAM_CAPACITY: usize = 8 * 1024;
+
+/// Reads bits from an [`io::Read`] source, such as a file, without holding all of it in memory.
+///
+/// The source is read into a buffer as reads need more bits, and the bytes before the cursor are dropped when it
+/// is refilled. Reads have the same API and the same all-or-nothing behaviour as [`BitVecReader`], with error
+/// offsets counted from the start of the stream. A read that runs into the end of the source fails with
+/// [`ReadError::OutOfBounds`], and an error from the source is returned as [`ReadError::Io`].
+#[derive(Debug)]
+pub struct StreamBitReader<R: Read, O: BitOrder> {
+	source: R,
+	buffer: BitVecReader<O>,
+	/// The number of bits dropped from the front of the buffer.
+	dropped: usize,
+	capacity: usize,
+	eof: bool,
+	/// The position refills must keep buffered while a read runs [`atomically`](StreamBitReader::atomically),
+	/// counted from the start of the stream.
+	pinned: Option<usize>,
+}
+impl<R: Read, O: BitOrder> StreamBitReader<R, O>
+where
+	BitSlice<O, u8>: BitField + LoadBits<O>,
+{
+	#[inline]
+	pub fn new(source: R) -> StreamBitReader<R, O> {
+		StreamBitReader::with_capacity(source, DEFAULT_STREAM_CAPACITY)
+	}
+
+	/// Creates a reader that reads `capacity` bytes from the source at a time.
+	#[inline]
+	pub fn with_capacity(source: R, capacity: usize) -> StreamBitReader<R, O> {
+		StreamBitReader {
+			source,
+			buffer: BitVecReader::new(BitVec::new()),
+			dropped: 0,
+			capacity: capacity.max(1),
+			eof: false,
+			pinned: None,
+		}
+	}
+
+	#[inline]
+	pub fn get_ref(&self) -> &R {
+		&self.source
+	}
+
+	/// Returns the source. Any bytes that have been buffered but not read are lost.
+	#[inline]
+	pub fn into_inner(self) -> R {
+		self.source
+	}
+
+	/// Returns the number of bits read from the start of the stream.
+	#[inline]
+	pub fn bits_read(&self) -> usize {
+		self.dropped + self.buffer.cursor
+	}
+
+	/// Returns the number of bits that have been buffered but not read yet.
+	#[inline]
+	pub fn bits_buffered(&self) -> usize {
+		self.buffer.bits_left()
+	}
+
+	/// Returns whether the source has run out, and every bit left is buffered.
+	#[inline]
+	pub fn is_eof(&self) -> bool {
+		self.eof
+	}
+
+	/// Drops the bytes before the cursor, or before the pinned position, and reads at least one more byte from the
+	/// source into the buffer, unless it has run out.
+	fn refill(&mut self) -> Result<(), ReadError> {
+		let keep = match self.pinned {
+			Some(pinned) => (pinned - self.dropped).min(self.buffer.cursor),
+			None => self.buffer.cursor,
+		};
+		let mut bytes = std::mem::take(&mut self.buffer.bitvec).into_vec();
+		let drop = keep / 8;
+		bytes.drain(..drop);
+		self.dropped += drop * 8;
+		self.buffer.cursor -= drop * 8;
+
+		let len = bytes.len();
+		bytes.resize(len + self.capacity.max(len), 0);
+		let result = loop {
+			match self.source.read(&mut bytes[len..]) {
+				Ok(n) => break Ok(n),
+				Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+				Err(error) => break Err(error),
+			}
+		};
+		let read = *result.as_ref().unwrap_or(&0);
+		bytes.truncate(len + read);
+		self.buffer.bitvec = BitVec::from_vec(bytes);
+
+		match result {
+			Ok(0) => {
+				self.eof = true;
+				Ok(())
+			}
+			Ok(_) => Ok(()),
+			Err(error) => Err(ReadError::Io {
+				offset: self.bits_read(),
+				kind: error.kind(),
+			}),
+		}
+	}
+
+	/// Runs `read` against the buffer, refilling it and trying again for as long as the read runs out of bits and
+	/// the source has more.
+	fn read_with<T>(
+		&mut self,
+		mut read: impl FnMut(&mut BitVecReader<O>) -> Result<T, ReadError>,
+	) -> Result<T, ReadError> {
+		loop {
+			match read(&mut self.buffer) {
+				Err(ReadError::OutOfBounds { .. }) if !self.eof => self.refill()?,
+				result => return result.map_err(|error| error.offset_by(self.dropped)),
+			}
+		}
+	}
+
+	/// Skips `bits` bits, reading past them in the source if they aren't buffered.
+	///
+	/// Unlike a read, a skip past the end of the stream leaves the cursor at the end of the stream.
+	pub fn skip(&mut self, bits: usize) -> Result<(), ReadError> {
+		let buffered = self.buffer.bits_left();
+		if bits <= buffered {
+			self.buffer.advance(bits);
+			return Ok(());
+		}
+
+		// A read running atomically may need to move back over the skipped bits, so they have to be buffered
+		if self.pinned.is_some() {
+			return self.read_with(|reader| {
+				let available = reader.bits_left();
+				if bits > available {
+					return Err(ReadError::OutOfBounds {
+						offset: reader.cursor,
+						requested: bits,
+						available,
+					});
+				}
+				reader.advance(bits);
+				Ok(())
+			});
+		}
+
+		// Skip the whole bytes past the buffer without buffering them
+		let offset = self.bits_read();
+		let skip_bytes = (bits - buffered) / 8;
+		let bits_left = (bits - buffered) % 8;
+		self.dropped += self.buffer.bits_len();
+		self.buffer = BitVecReader::new(BitVec::new());
+		let skipped = io::copy(
+			&mut (&mut self.source).take(skip_bytes as u64),
+			&mut io::sink(),
+		)
+		.map_err(|error| ReadError::Io {
+			offset,
+			kind: error.kind(),
+		})? as usize;
+		self.dropped += skipped * 8;
+		if skipped < skip_bytes {
+			self.eof = true;
+			return Err(ReadError::OutOfBounds {
+				offset,
+				requested: bits,
+				available: buffered + skipped * 8,
+			});
+		}
+
+		if bits_left > 0 {
+			self.read_with(|reader| reader.read_uint::<u8>(bits_left))
+				.map_err(|error| match error {
+					ReadError::OutOfBounds { available, .. } => ReadError::OutOfBounds {
+						offset,
+						requested: bits,
+						available: bits - bits_left + available,
+					},
+					error => error,
+				})?;
+		}
+		Ok(())
+	}
+
+	#[inline]
+	pub fn read_uint<N>(&mut self, bits: usize) -> Result<N, ReadError>
+	where
+		N: BitMemory + IsNumber + IsUnsigned,
+	{
+		self.read_with(|reader| reader.read_uint(bits))
+	}
+
+	#[inline]
+	pub fn read_int<N>(&mut self, bits: usize) -> Result<N, ReadError>
+	where
+		N: FromBitMemory + IsNumber + IsSigned,
+	{
+		self.read_with(|reader| reader.read_int(bits))
+	}
+
+	/// Runs a read made up of several smaller reads, moving the cursor back if any of them fail. Nothing it reads is
+	/// dropped from the buffer until it is done.
+	fn atomically<T>(
+		&mut self,
+		read: impl FnOnce(&mut Self) -> Result<T, ReadError>,
+	) -> Result<T, ReadError> {
+		let start = self.bits_read();
+		let outer = self.pinned;
+		self.pinned = Some(outer.unwrap_or(start));
+		let result = read(self);
+		self.pinned = outer;
+		if result.is_err() {
+			self.buffer.cursor = start - self.dropped;
+		}
+		result
+	}
+}
+
+macro_rules! forward_reads {
+	( $( $(#[$attr:meta])* fn $name:ident(&mut self $(, $arg:ident: $ty:ty)*) -> $ret:ty; )* ) => {
+		impl<R: Read, O: BitOrder> StreamBitReader<R, O>
+		where
+			BitSlice<O, u8>: BitField + LoadBits<O>,
+		{$(
+			$(#[$attr])*
+			#[doc = concat!("See [`BitVecReader::", stringify!($name), "`].")]
+			#[inline]
+			pub fn $name(&mut self $(, $arg: $ty)*) -> Result<$ret, ReadError> {
+				self.read_with(|reader| reader.$name($($arg),*))
+			}
+		)*}
+	};
+}
+forward_reads! {
+	fn read_bit(&mut self) -> bool;
+	fn read_byte(&mut self) -> u8;
+	fn read_bytes(&mut self, len: usize) -> Vec<u8>;
+	fn read_uints_into(&mut self, out: &mut [u32], bits: usize) -> ();
+	fn read_float(&mut self) -> f32;
+	#[cfg(target_pointer_width = "64")]
+	fn read_double(&mut self) -> f64;
+	fn read_varint32(&mut self) -> u32;
+	fn read_varint64(&mut self) -> u64;
+	fn read_signed_varint32(&mut self) -> i32;
+	fn read_signed_varint64(&mut self) -> i64;
+	fn read_ubitvar(&mut self) -> u32;
+	fn read_field_index(&mut self, last_index: i32, new_way: bool) -> Option<i32>;
+	fn read_bit_coord(&mut self) -> f32;
+	fn read_bit_coord_mp(&mut self, integral: bool, low_precision: bool) -> f32;
+	fn read_bit_normal(&mut self) -> f32;
+	fn read_bit_angle(&mut self, bits: usize) -> f32;
+	fn read_bit_vec3_coord(&mut self) -> [f32; 3];
+	fn read_bit_vec3_normal(&mut self) -> [f32; 3];
+	fn read_string(&mut self, bytes: usize) -> String;
+	fn read_string_lossy(&mut self, bytes: usize) -> String;
+	fn read_nul_string(&mut self) -> String;
+	fn read_nul_string_lossy(&mut self) -> String;
+}
+
+impl<R: Read, O: BitOrder> BitRead for StreamBitReader<R, O>
+where
+	BitSlice<O, u8>: BitField + LoadBits<O>,
+{
+	/// Returns `None` until the source has run out.
+	#[inline]
+	fn bits_left(&self) -> Option<usize> {
+		self.eof.then(|| self.buffer.bits_left())
+	}
+
+	forward_bit_read!();
+}
+
+impl<R: Read + Seek, O: BitOrder> StreamBitReader<R, O>
+where
+	BitSlice<O, u8>: BitField + LoadBits<O>,
+{
+	/// Moves the cursor to `bit` bits from the start of the stream, seeking the source if it isn't buffered.
+	///
+	/// While a read is running [atomically](BitRead::atomically), seeking back before the buffer fails with an
+	/// [`io::ErrorKind::InvalidInput`] error instead, as the buffer holds the bits the read would move back over.
+	pub fn seek_bits(&mut self, bit: usize) -> Result<(), ReadError> {
+		if bit >= self.dropped && bit - self.dropped <= self.buffer.bits_len() {
+			self.buffer.cursor = bit - self.dropped;
+			return Ok(());
+		}
+		// Seeking ahead would drop the bits a read running atomically may need to move back over, so skip to it
+		if self.pinned.is_some() && bit > self.bits_read() {
+			return self.skip(bit - self.bits_read());
+		}
+		// And seeking back before the buffer would drop them too
+		if self.pinned.is_some() && bit < self.dropped {
+			return Err(ReadError::Io {
+				offset: self.bits_read(),
+				kind: io::ErrorKind::InvalidInput,
+			});
+		}
+
+		let byte = bit / 8;
+		self.source
+			.seek(SeekFrom::Start(byte as u64))
+			.map_err(|error| ReadError::Io {
+				offset: self.bits_read(),
+				kind: error.kind(),
+			})?;
+		self.buffer = BitVecReader::new(BitVec::new());
+		self.dropped = byte * 8;
+		self.eof = false;
+		let bits_left = bit % 8;
+		if bits_left > 0 {
+			self.read_with(|reader| reader.read_uint::<u8>(bits_left))?;
+		}
+		Ok(())
+	}
+}
+
+/// Writes bits to an [`io::Write`] sink, such as a file, without holding all of them in memory.
+///
+/// Every byte is written to the sink as soon as it is complete, so only the trailing partial byte is kept in
+/// memory; wrap the sink in an [`io::BufWriter`] to batch the writes. Writes have the same API as [`BitVecWriter`].
+/// An error from the sink is sticky: every further write is dropped, and the error is returned by
+/// [`finish`](StreamBitWriter::finish).
+///
+/// The trailing partial byte is only written by [`finish`](StreamBitWriter::finish), so a writer that is dropped
+/// without finishing loses up to 7 bits.
+#[derive(Debug)]
+pub struct StreamBitWriter<W: Write, O: BitOrder> {
+	sink: W,
+	buffer: BitVecWriter<O>,
+	/// The number of bits written to the sink.
+	flushed: usize,
+	error: Option<io::Error>,
+}
+impl<W: Write, O: BitOrder> StreamBitWriter<W, O>
+where
+	BitSlice<O, u8>: BitField + StoreBits<O>,
+{
+	#[inline]
+	pub fn new(sink: W) -> StreamBitWriter<W, O> {
+		StreamBitWriter {
+			sink,
+			buffer: BitVecWriter::new(BitVec::new()),
+			flushed: 0,
+			error: None,
+		}
+	}
+
+	#[inline]
+	pub fn get_ref(&self) -> &W {
+		&self.sink
+	}
+
+	/// Returns the number of bits written from the start of the stream, including the trailing partial byte.
+	#[inline]
+	pub fn bits_written(&self) -> usize {
+		self.flushed + self.buffer.bitvec.len()
+	}
+
+	/// Returns the number of bits in the trailing partial byte, which haven't been written to the sink yet.
+	#[inline]
+	pub fn bits_pending(&self) -> usize {
+		self.buffer.bitvec.len()
+	}
+
+	/// Returns the error the sink failed with, if any.
+	#[inline]
+	pub fn error(&self) -> Option<&io::Error> {
+		self.error.as_ref()
+	}
+
+	/// Pads the trailing partial byte with zero bits, writes it, flushes the sink and returns it.
+	pub fn finish(mut self) -> io::Result<W> {
+		let len = self.buffer.bitvec.len();
+		let partial = len % 8;
+		if partial > 0 {
+			self.buffer.bitvec.resize(len + 8 - partial, false);
+		}
+		self.flush_bytes();
+		match self.error {
+			Some(error) => Err(error),
+			None => {
+				self.sink.flush()?;
+				Ok(self.sink)
+			}
+		}
+	}
+
+	/// Writes the complete bytes in the buffer to the sink, keeping only the trailing partial byte.
+	fn flush_bytes(&mut self) {
+		let len = self.buffer.bitvec.len();
+		let whole = len / 8;
+		if whole == 0 || self.error.is_some() {
+			return;
+		}
+
+		let mut bytes = std::mem::take(&mut self.buffer.bitvec).into_vec();
+		if let Err(error) = self.sink.write_all(&bytes[..whole]) {
+			self.error = Some(error);
+		}
+		bytes.drain(..whole);
+		let mut tail = BitVec::from_vec(bytes);
+		tail.truncate(len - whole * 8);
+		self.buffer = BitVecWriter::new(tail);
+		self.flushed += whole * 8;
+	}
+
+	/// Runs `write` against the buffer and writes the bytes it completes to the sink, unless the sink has failed.
+	fn write_with(&mut self, write: impl FnOnce(&mut BitVecWriter<O>)) {
+		if self.error.is_none() {
+			write(&mut self.buffer);
+			self.flush_bytes();
+		}
+	}
+
+	#[inline]
+	pub fn write_bytes<B: AsRef<[u8]>>(&mut self, bytes: B) {
+		self.write_with(|writer| writer.write_bytes(bytes))
+	}
+
+	#[inline]
+	pub fn write_uint<N>(&mut self, uint: N, bits: usize)
+	where
+		N: BitMemory + IsNumber + IsUnsigned,
+	{
+		self.write_with(|writer| writer.write_uint(uint, bits))
+	}
+
+	#[inline]
+	pub fn write_int<N>(&mut self, int: N, bits: usize)
+	where
+		N: IsNumber + IsSigned + IntoBitView,
+	{
+		self.write_with(|writer| writer.write_int(int, bits))
+	}
+
+	#[inline]
+	pub fn write_string<S: AsRef<str>>(&mut self, str: S) {
+		self.write_with(|writer| writer.write_string(str))
+	}
+
+	#[inline]
+	pub fn write_string_nul<S: AsRef<str>>(&mut self, str: S) {
+		self.write_with(|writer| writer.write_string_nul(str))
+	}
+}
+
+macro_rules! forward_writes {
+	( $( $(#[$attr:meta])* fn $name:ident(&mut self $(, $arg:ident: $ty:ty)*); )* ) => {
+		impl<W: Write, O: BitOrder> StreamBitWriter<W, O>
+		where
+			BitSlice<O, u8>: BitField + StoreBits<O>,
+		{$(
+			$(#[$attr])*
+			#[doc = concat!("See [`BitVecWriter::", stringify!($name), "`].")]
+			#[inline]
+			pub fn $name(&mut self $(, $arg: $ty)*) {
+				self.write_with(|writer| writer.$name($($arg),*))
+			}
+		)*}
+	};
+}
+forward_writes! {
+	fn write_bit(&mut self, bit: bool);
+	fn write_byte(&mut self, byte: u8);
+	fn write_uints(&mut self, uints: &[u32], bits: usize);
+	fn write_float(&mut self, float: f32);
+	#[cfg(target_pointer_width = "64")]
+	fn write_double(&mut self, double: f64);
+	fn write_varint32(&mut self, varint: u32);
+	fn write_varint64(&mut self, varint: u64);
+	fn write_signed_varint32(&mut self, varint: i32);
+	fn write_signed_varint64(&mut self, varint: i64);
+	fn write_ubitvar(&mut self, uint: u32);
+	fn write_field_index(&mut self, last_index: i32, index: Option<i32>, new_way: bool);
+	fn write_bit_coord(&mut self, coord: f32);
+	fn write_bit_coord_mp(&mut self, coord: f32, integral: bool, low_precision: bool);
+	fn write_bit_normal(&mut self, normal: f32);
+	fn write_bit_angle(&mut self, angle: f32, bits: usize);
+	fn write_bit_vec3_coord(&mut self, vec: [f32; 3]);
+	fn write_bit_vec3_normal(&mut self, vec: [f32; 3]);
+}
+
+impl<W: Write, O: BitOrder> BitWrite for StreamBitWriter<W, O>
+where
+	BitSlice<O, u8>: BitField + StoreBits<O>,
+{
+	forward_bit_write!();
+}
+
+/// Writes whole bytes at the cursor, which doesn't need to be byte-aligned.
+impl<W: Write, O: BitOrder> Write for StreamBitWriter<W, O>
+where
+	BitSlice<O, u8>: BitField + StoreBits<O>,
+{
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		if let Some(error) = &self.error {
+			return Err(error.kind().into());
+		}
+		self.write_bytes(buf);
+		match &self.error {
+			Some(error) => Err(error.kind().into()),
+			None => Ok(buf.len()),
+		}
+	}
+
+	/// Flushes the sink. The trailing partial byte is kept until [`finish`](StreamBitWriter::finish).
+	fn flush(&mut self) -> io::Result<()> {
+		match &self.error {
+			Some(error) => Err(error.kind().into()),
+			None => self.sink.flush(),
+		}
+	}
+}
@@ -0,0 +1,190 @@
+use crate::CachedBitReader;
+
+/// Deterministic bytes that aren't all zero.
+fn noise(len: usize) -> Vec<u8> {
+	let mut state = 0x2545_F491_4F6C_DD1D_u64;
+	(0..len)
+		.map(|_| {
+			state ^= state << 13;
+			state ^= state >> 7;
+			state ^= state << 17;
+			state as u8
+		})
+		.collect()
+}
+
+test!(
+	test_cached_reader_uints_lsb,
+	test_cached_reader_uints_msb,
+	bench_cached_reader_uints_lsb,
+	bench_cached_reader_uints_msb,
+	{
+		let bytes = noise(4096);
+		let mut expected = BitVecReader::<Endian>::from_bytes(bytes.clone());
+		let mut bitbuf = CachedBitReader::<Endian>::new(&bytes);
+
+		// Every width, at every alignment, through every refill
+		let mut bits = 1;
+		while expected.bits_left() > 128 {
+			assert_eq!(
+				bitbuf.read_uint::<u8>(bits % 8 + 1),
+				expected.read_uint::<u8>(bits % 8 + 1)
+			);
+			assert_eq!(
+				bitbuf.read_uint::<u32>(bits % 32 + 1),
+				expected.read_uint::<u32>(bits % 32 + 1)
+			);
+			assert_eq!(
+				bitbuf.read_uint::<u64>(bits % 64 + 1),
+				expected.read_uint::<u64>(bits % 64 + 1)
+			);
+			assert_eq!(
+				bitbuf.read_int::<i16>(bits % 16 + 1),
+				expected.read_int::<i16>(bits % 16 + 1)
+			);
+			assert_eq!(
+				bitbuf.read_int::<i64>(bits % 64 + 1),
+				expected.read_int::<i64>(bits % 64 + 1)
+			);
+			assert_eq!(bitbuf.read_bit(), expected.read_bit());
+			assert_eq!(bitbuf.cursor(), expected.cursor);
+			bits += 1;
+		}
+		assert_eq!(
+			bitbuf.read_uint::<u128>(100),
+			expected.read_uint::<u128>(100)
+		);
+		assert_eq!(bitbuf.read_uint::<u8>(9), expected.read_uint::<u8>(9));
+		assert_eq!(bitbuf.read_uint::<u8>(0), expected.read_uint::<u8>(0));
+		while expected.bits_left() >= 3 {
+			assert_eq!(bitbuf.read_uint::<u8>(3), expected.read_uint::<u8>(3));
+			assert_eq!(bitbuf.bits_left(), expected.bits_left());
+		}
+		assert_eq!(bitbuf.read_uint::<u8>(3), expected.read_uint::<u8>(3));
+		while expected.bits_left() > 0 {
+			assert_eq!(bitbuf.read_bit(), expected.read_bit());
+		}
+		assert_eq!(bitbuf.read_bit(), expected.read_bit());
+	}
+);
+
+test!(
+	test_cached_reader_read_uints_lsb,
+	test_cached_reader_read_uints_msb,
+	bench_cached_reader_read_uints_lsb,
+	bench_cached_reader_read_uints_msb,
+	{
+		let bytes = noise(2048);
+		let mut expected = BitVecReader::<Endian>::from_bytes(bytes.clone());
+		let mut bitbuf = CachedBitReader::<Endian>::new(&bytes);
+		for bits in 1..=32 {
+			let (mut out, mut expected_out) = (vec![0; bits + 3], vec![0; bits + 3]);
+			assert_eq!(
+				bitbuf.read_uints_into(&mut out, bits),
+				expected.read_uints_into(&mut expected_out, bits)
+			);
+			assert_eq!(out, expected_out);
+		}
+		assert_eq!(bitbuf.cursor(), expected.cursor);
+
+		let mut out = vec![0; 1000];
+		assert_eq!(
+			bitbuf.read_uints_into(&mut out, 32),
+			expected.read_uints_into(&mut out, 32)
+		);
+		assert_eq!(out, vec![0; 1000]);
+	}
+);
+
+test!(
+	test_cached_reader_lsb,
+	test_cached_reader_msb,
+	bench_cached_reader_lsb,
+	bench_cached_reader_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		bitbuf.write_float(1.5);
+		bitbuf.write_double(-0.25);
+		bitbuf.write_varint64(u64::MAX);
+		bitbuf.write_signed_varint32(-300);
+		bitbuf.write_ubitvar(1000);
+		bitbuf.write_field_index(4, Some(100), true);
+		bitbuf.write_bit_vec3_coord([1.5, -2.25, 0.]);
+		bitbuf.write_bit_vec3_normal([0.6, -0.8, 0.]);
+		bitbuf.write_bit_angle(90., 8);
+		bitbuf.write_string_nul("Hello, world!");
+		bitbuf.write_bytes([1, 2, 3]);
+		bitbuf.write_uint(0_u8, 7);
+		bitbuf.write_bytes([4, 5]);
+		bitbuf.write_string("ß");
+		bitbuf.write_bytes([0xFF, 0xFF]);
+		let bytes = bitbuf.into_bytes();
+
+		let mut expected = BitVecReader::<Endian>::from_bytes(bytes.clone());
+		let mut bitbuf = CachedBitReader::<Endian>::new(&bytes);
+		assert_eq!(bitbuf.read_bit(), expected.read_bit());
+		assert_eq!(bitbuf.read_float(), expected.read_float());
+		assert_eq!(bitbuf.read_double(), expected.read_double());
+		assert_eq!(bitbuf.read_varint64(), expected.read_varint64());
+		assert_eq!(
+			bitbuf.read_signed_varint32(),
+			expected.read_signed_varint32()
+		);
+		assert_eq!(bitbuf.read_ubitvar(), expected.read_ubitvar());
+		assert_eq!(
+			bitbuf.read_field_index(4, true),
+			expected.read_field_index(4, true)
+		);
+		assert_eq!(bitbuf.read_bit_vec3_coord(), expected.read_bit_vec3_coord());
+		assert_eq!(
+			bitbuf.read_bit_vec3_normal(),
+			expected.read_bit_vec3_normal()
+		);
+		assert_eq!(bitbuf.read_bit_angle(8), expected.read_bit_angle(8));
+		assert_eq!(bitbuf.peek_bit(), expected.peek_bit());
+		assert_eq!(bitbuf.read_nul_string(), expected.read_nul_string());
+		assert_eq!(bitbuf.peek_bytes(3), expected.peek_bytes(3));
+		assert_eq!(bitbuf.read_bytes(3), expected.read_bytes(3));
+		assert_eq!(bitbuf.read_uint::<u8>(7), expected.read_uint::<u8>(7));
+		assert_eq!(bitbuf.read_bytes(2), expected.read_bytes(2));
+		assert_eq!(bitbuf.read_string(2), expected.read_string(2));
+
+		// Errors, which leave the cursor where it was
+		assert_eq!(bitbuf.read_string(2), expected.read_string(2));
+		assert_eq!(bitbuf.read_nul_string(), expected.read_nul_string());
+		assert_eq!(bitbuf.read_varint32(), expected.read_varint32());
+		assert_eq!(bitbuf.read_bytes(3), expected.read_bytes(3));
+		assert_eq!(
+			bitbuf.read_bytes(usize::MAX),
+			expected.read_bytes(usize::MAX)
+		);
+		assert_eq!(bitbuf.read_uint::<u8>(9), expected.read_uint::<u8>(9));
+		assert_eq!(bitbuf.cursor(), expected.cursor);
+
+		// Seeking, at and away from byte boundaries
+		bitbuf.seek(1);
+		expected.cursor = 1;
+		assert_eq!(bitbuf.read_float(), expected.read_float());
+		bitbuf.advance(3);
+		expected.advance(3);
+		bitbuf.rewind(2);
+		expected.rewind(2);
+		assert_eq!(bitbuf.read_uint::<u16>(16), expected.read_uint::<u16>(16));
+		bitbuf.advance(100);
+		expected.advance(100);
+		assert_eq!(bitbuf.read_uint::<u16>(16), expected.read_uint::<u16>(16));
+		assert_eq!(bitbuf.cursor(), expected.cursor);
+
+		// Nul strings found and missing, at and away from byte boundaries
+		for cursor in [0, 1, bitbuf.bits_len() - 16, bitbuf.bits_len() - 15].iter() {
+			bitbuf.seek(*cursor);
+			expected.cursor = *cursor;
+			assert_eq!(
+				bitbuf.read_nul_string_lossy(),
+				expected.read_nul_string_lossy()
+			);
+			assert_eq!(bitbuf.cursor(), expected.cursor);
+		}
+	}
+);
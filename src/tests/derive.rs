@@ -0,0 +1,189 @@
+use crate::{BitDecode, BitEncode, FromBits, ToBits};
+
+#[derive(Debug, PartialEq, BitEncode, BitDecode)]
+struct Header {
+	#[bits(12)]
+	id: u16,
+	#[bits(5)]
+	delta: i8,
+	#[varint]
+	length: u32,
+	#[varint]
+	offset: i64,
+	has_name: bool,
+	#[cond(*has_name)]
+	#[nul_string]
+	name: Option<String>,
+	#[skip]
+	cached: u32,
+	kind: Kind,
+	scale: f32,
+}
+
+#[derive(Debug, PartialEq, BitEncode, BitDecode)]
+#[bits(3)]
+#[repr(u8)]
+enum Kind {
+	Empty,
+	Id(#[bits(4)] u8),
+	Point { x: i16, y: i16 } = 5,
+	Last,
+}
+
+fn header() -> Header {
+	Header {
+		id: 0xABC,
+		delta: -7,
+		length: 300,
+		offset: -2,
+		has_name: true,
+		name: Some("hello".to_string()),
+		cached: 0,
+		kind: Kind::Point { x: -1, y: 1024 },
+		scale: 1.5,
+	}
+}
+
+test!(
+	test_derive_roundtrip_lsb,
+	test_derive_roundtrip_msb,
+	bench_derive_roundtrip_lsb,
+	bench_derive_roundtrip_msb,
+	{
+		let mut nameless = header();
+		nameless.has_name = false;
+		nameless.name = None;
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		header().to_bits(&mut bitbuf);
+		nameless.to_bits(&mut bitbuf);
+		Kind::Id(9).to_bits(&mut bitbuf);
+		Kind::Last.to_bits(&mut bitbuf);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(Header::from_bits(&mut bitbuf), Ok(header()));
+		assert_eq!(Header::from_bits(&mut bitbuf), Ok(nameless));
+		assert_eq!(Kind::from_bits(&mut bitbuf), Ok(Kind::Id(9)));
+		assert_eq!(Kind::from_bits(&mut bitbuf), Ok(Kind::Last));
+		assert!(bitbuf.bits_left() < 8);
+	}
+);
+
+test!(
+	test_derive_layout_lsb,
+	test_derive_layout_msb,
+	bench_derive_layout_lsb,
+	bench_derive_layout_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		header().to_bits(&mut bitbuf);
+		assert_eq!(
+			bitbuf.bits_written(),
+			12 + 5 + 16 + 8 + 1 + 48 + 3 + 32 + 32
+		);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read_uint::<u16>(12), Ok(0xABC));
+		assert_eq!(bitbuf.read_int::<i8>(5), Ok(-7));
+		assert_eq!(bitbuf.read_varint32(), Ok(300));
+		assert_eq!(bitbuf.read_signed_varint64(), Ok(-2));
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.read_nul_string(), Ok("hello".to_string()));
+		assert_eq!(bitbuf.read_uint::<u8>(3), Ok(5));
+		assert_eq!(bitbuf.read_int::<i16>(16), Ok(-1));
+		assert_eq!(bitbuf.read_int::<i16>(16), Ok(1024));
+		assert_eq!(bitbuf.read_float(), Ok(1.5));
+	}
+);
+
+test!(
+	test_derive_errors_lsb,
+	test_derive_errors_msb,
+	bench_derive_errors_lsb,
+	bench_derive_errors_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_uint(7_u8, 3);
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(
+			Kind::from_bits(&mut bitbuf),
+			Err(ReadError::InvalidValue {
+				offset: 0,
+				value: 7
+			})
+		);
+		assert_eq!(bitbuf.cursor, 0);
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		header().to_bits(&mut bitbuf);
+		let mut bytes = bitbuf.into_bytes();
+		bytes.truncate(8);
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bytes);
+		assert!(Header::from_bits(&mut bitbuf).is_err());
+		assert_eq!(bitbuf.cursor, 0);
+	}
+);
+
+#[derive(Debug, PartialEq, BitEncode, BitDecode)]
+#[bits(4)]
+enum Signed {
+	Low = -2,
+	Minus,
+	Zero,
+	High = 7,
+}
+
+test!(
+	test_derive_negative_discriminant_lsb,
+	test_derive_negative_discriminant_msb,
+	bench_derive_negative_discriminant_lsb,
+	bench_derive_negative_discriminant_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		for signed in [Signed::Low, Signed::Minus, Signed::Zero, Signed::High] {
+			signed.to_bits(&mut bitbuf);
+		}
+		assert_eq!(bitbuf.bits_written(), 4 * 4);
+
+		let bytes = bitbuf.into_bytes();
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bytes.clone());
+		assert_eq!(bitbuf.read_uint::<u8>(4), Ok(14));
+		assert_eq!(bitbuf.read_uint::<u8>(4), Ok(15));
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bytes);
+		assert_eq!(Signed::from_bits(&mut bitbuf), Ok(Signed::Low));
+		assert_eq!(Signed::from_bits(&mut bitbuf), Ok(Signed::Minus));
+		assert_eq!(Signed::from_bits(&mut bitbuf), Ok(Signed::Zero));
+		assert_eq!(Signed::from_bits(&mut bitbuf), Ok(Signed::High));
+	}
+);
+
+#[derive(Debug, PartialEq, BitEncode, BitDecode)]
+struct Snapshot<T> {
+	tick: u32,
+	entities: Vec<(u16, Kind)>,
+	position: [f32; 3],
+	owner: Option<String>,
+	extra: T,
+}
+
+test!(
+	test_derive_containers_lsb,
+	test_derive_containers_msb,
+	bench_derive_containers_lsb,
+	bench_derive_containers_msb,
+	{
+		let snapshot = Snapshot {
+			tick: 42,
+			entities: vec![(1, Kind::Empty), (2, Kind::Id(3))],
+			position: [1., -2., 0.5],
+			owner: Some("admin".to_string()),
+			extra: vec![header()],
+		};
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write(&snapshot);
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read::<Snapshot<Vec<Header>>>(), Ok(snapshot));
+	}
+);
@@ -0,0 +1,170 @@
+use crate::{BitRead, BitWrite, FromBits, ToBits};
+
+/// A user type with its own encoding, used inside the crate's containers.
+#[derive(Debug, PartialEq)]
+struct Point {
+	x: i16,
+	y: i16,
+}
+impl ToBits for Point {
+	fn to_bits<W: BitWrite>(&self, writer: &mut W) {
+		writer.write_int(self.x, 12);
+		writer.write_int(self.y, 12);
+	}
+}
+impl FromBits for Point {
+	fn from_bits<R: BitRead>(reader: &mut R) -> Result<Self, crate::ReadError> {
+		Ok(Point {
+			x: reader.read_int(12)?,
+			y: reader.read_int(12)?,
+		})
+	}
+}
+
+test!(
+	test_encode_roundtrip_lsb,
+	test_encode_roundtrip_msb,
+	bench_encode_roundtrip_lsb,
+	bench_encode_roundtrip_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		bitbuf.write(&0xAB_u8);
+		bitbuf.write(&-300_i16);
+		bitbuf.write(&u64::MAX);
+		bitbuf.write(&(u128::MAX - 1));
+		bitbuf.write(&i128::MIN);
+		bitbuf.write(&usize::MAX);
+		bitbuf.write(&-1_isize);
+		bitbuf.write(&false);
+		bitbuf.write(&'ß');
+		bitbuf.write(&1.5_f32);
+		bitbuf.write(&-0.25_f64);
+		bitbuf.write(&[1_u16, 2, 3]);
+		bitbuf.write(&(7_u8, true, Some(-1_i32)));
+		bitbuf.write(&None::<u64>);
+		bitbuf.write(&vec![Point { x: -5, y: 2047 }, Point { x: 0, y: -2048 }]);
+		bitbuf.write("Hello");
+		bitbuf.write(&"world".to_string());
+		bitbuf.write(&[1_u8, 2][..]);
+		bitbuf.write(&());
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read::<bool>(), Ok(true));
+		assert_eq!(bitbuf.read::<u8>(), Ok(0xAB));
+		assert_eq!(bitbuf.read::<i16>(), Ok(-300));
+		assert_eq!(bitbuf.read::<u64>(), Ok(u64::MAX));
+		assert_eq!(bitbuf.read::<u128>(), Ok(u128::MAX - 1));
+		assert_eq!(bitbuf.read::<i128>(), Ok(i128::MIN));
+		assert_eq!(bitbuf.read::<u64>(), Ok(usize::MAX as u64));
+		assert_eq!(bitbuf.read::<isize>(), Ok(-1));
+		assert_eq!(bitbuf.read::<bool>(), Ok(false));
+		assert_eq!(bitbuf.read::<char>(), Ok('ß'));
+		assert_eq!(bitbuf.read::<f32>(), Ok(1.5));
+		assert_eq!(bitbuf.read::<f64>(), Ok(-0.25));
+		assert_eq!(bitbuf.read::<[u16; 3]>(), Ok([1, 2, 3]));
+		assert_eq!(
+			bitbuf.read::<(u8, bool, Option<i32>)>(),
+			Ok((7, true, Some(-1)))
+		);
+		assert_eq!(bitbuf.read::<Option<u64>>(), Ok(None));
+		assert_eq!(
+			bitbuf.read::<Vec<Point>>(),
+			Ok(vec![Point { x: -5, y: 2047 }, Point { x: 0, y: -2048 }])
+		);
+		assert_eq!(bitbuf.read::<String>(), Ok("Hello".to_string()));
+		assert_eq!(bitbuf.read::<String>(), Ok("world".to_string()));
+		assert_eq!(bitbuf.read::<Vec<u8>>(), Ok(vec![1, 2]));
+		assert_eq!(bitbuf.read::<()>(), Ok(()));
+		assert!(bitbuf.bits_left() < 8);
+	}
+);
+
+test!(
+	test_encode_layout_lsb,
+	test_encode_layout_msb,
+	bench_encode_layout_lsb,
+	bench_encode_layout_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write(&Some(3_u8));
+		bitbuf.write(&vec![true, false]);
+		bitbuf.write("hi");
+		bitbuf.write(&'a');
+		assert_eq!(bitbuf.bits_written(), 1 + 8 + 8 + 2 + 8 + 16 + 32);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.read_uint::<u8>(8), Ok(3));
+		assert_eq!(bitbuf.read_varint64(), Ok(2));
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.read_bit(), Ok(false));
+		assert_eq!(bitbuf.read_varint64(), Ok(2));
+		assert_eq!(bitbuf.read_string(2), Ok("hi".to_string()));
+		assert_eq!(bitbuf.read_uint::<u32>(32), Ok('a' as u32));
+	}
+);
+
+test!(
+	test_encode_errors_lsb,
+	test_encode_errors_msb,
+	bench_encode_errors_lsb,
+	bench_encode_errors_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		bitbuf.write_uint(0xD800_u32, 32);
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(
+			bitbuf.read::<Option<char>>(),
+			Err(ReadError::InvalidValue {
+				offset: 1,
+				value: 0xD800
+			})
+		);
+		assert_eq!(bitbuf.cursor, 0);
+
+		// A length longer than what's left fails without allocating for it
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_varint64(u64::MAX);
+		bitbuf.write(&[1_u8, 2, 3]);
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert!(matches!(
+			bitbuf.read::<Vec<u8>>(),
+			Err(ReadError::OutOfBounds { .. })
+		));
+		assert_eq!(bitbuf.cursor, 0);
+		assert_eq!(
+			bitbuf.read::<String>(),
+			Err(ReadError::InvalidValue {
+				offset: 0,
+				value: u64::MAX
+			})
+		);
+		assert_eq!(bitbuf.cursor, 0);
+
+		// Past the first bit, a huge string length can't overflow the range it would be read from
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_byte(0xFF);
+		bitbuf.write_varint64(usize::MAX as u64 / 8);
+		bitbuf.write_string("abc");
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read_byte(), Ok(0xFF));
+		assert_eq!(
+			bitbuf.read::<String>(),
+			Err(ReadError::InvalidValue {
+				offset: 8,
+				value: usize::MAX as u64 / 8
+			})
+		);
+		assert_eq!(bitbuf.cursor, 8);
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write(&vec![1_u16, 2, 3]);
+		let mut bytes = bitbuf.into_bytes();
+		bytes.pop();
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bytes);
+		assert!(bitbuf.read::<Vec<u16>>().is_err());
+		assert_eq!(bitbuf.cursor, 0);
+	}
+);
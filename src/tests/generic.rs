@@ -0,0 +1,336 @@
+use std::io::{self, Cursor};
+
+use bitvec::{
+	macros::internal::funty::{IsNumber, IsUnsigned},
+	mem::BitMemory,
+};
+
+use crate::{BitRead, BitWrite, ReadError};
+
+use super::stream::Trickle;
+
+#[derive(Debug, PartialEq)]
+struct Packet {
+	flag: bool,
+	id: u16,
+	delta: i8,
+	scale: f32,
+	precise: f64,
+	seq: u64,
+	payload: Vec<u8>,
+	name: String,
+	tags: Vec<(u8, String)>,
+	origin: [f32; 3],
+}
+
+fn packet() -> Packet {
+	Packet {
+		flag: true,
+		id: 0x3FF,
+		delta: -5,
+		scale: 1.25,
+		precise: -0.1,
+		seq: u64::MAX - 1,
+		payload: vec![0, 1, 0xFE, 0xFF],
+		name: "Hello, world!".to_string(),
+		tags: vec![(1, "a".to_string()), (2, "bc".to_string())],
+		origin: [1.5, -2.25, 0.],
+	}
+}
+
+/// One encoder for every writer.
+fn encode<W: BitWrite>(writer: &mut W, packet: &Packet) {
+	writer.write_bit(packet.flag);
+	writer.write_uint(packet.id, 10);
+	writer.write_int(packet.delta, 5);
+	writer.write_float(packet.scale);
+	writer.write_double(packet.precise);
+	writer.write_varint64(packet.seq);
+	writer.write_uint(packet.payload.len() as u8, 8);
+	writer.write_bytes(&packet.payload);
+	writer.write_string_nul(&packet.name);
+	writer.write(&packet.tags);
+	writer.write_bit_vec3_coord(packet.origin);
+}
+
+/// One decoder for every reader.
+fn decode<R: BitRead>(reader: &mut R) -> Result<Packet, ReadError> {
+	reader.atomically(|reader| {
+		Ok(Packet {
+			flag: reader.read_bit()?,
+			id: reader.read_uint(10)?,
+			delta: reader.read_int(5)?,
+			scale: reader.read_float()?,
+			precise: reader.read_double()?,
+			seq: reader.read_varint64()?,
+			payload: {
+				let len = reader.read_uint::<u8>(8)?;
+				reader.read_bytes(len as usize)?
+			},
+			name: reader.read_nul_string()?,
+			tags: reader.read()?,
+			origin: reader.read_bit_vec3_coord()?,
+		})
+	})
+}
+
+/// A reader over one `bool` per bit, least significant bit first, that only implements what it has to.
+struct BoolReader {
+	bits: Vec<bool>,
+	cursor: usize,
+}
+impl BitRead for BoolReader {
+	fn bits_read(&self) -> usize {
+		self.cursor
+	}
+
+	fn bits_left(&self) -> Option<usize> {
+		Some(self.bits.len() - self.cursor)
+	}
+
+	fn read_bit(&mut self) -> Result<bool, ReadError> {
+		self.read_uint::<u8>(1).map(|bit| bit == 1)
+	}
+
+	fn read_uint<N>(&mut self, bits: usize) -> Result<N, ReadError>
+	where
+		N: BitMemory + IsNumber + IsUnsigned,
+	{
+		if bits > self.bits.len() - self.cursor {
+			return Err(ReadError::OutOfBounds {
+				offset: self.cursor,
+				requested: bits,
+				available: self.bits.len() - self.cursor,
+			});
+		}
+		let mut uint = 0_u128;
+		for (i, bit) in self.bits[self.cursor..self.cursor + bits]
+			.iter()
+			.enumerate()
+		{
+			uint |= (*bit as u128) << i;
+		}
+		self.cursor += bits;
+		Ok(N::try_from(uint).unwrap_or_else(|_| unreachable!()))
+	}
+}
+
+/// A writer into one `bool` per bit, least significant bit first, that only implements what it has to.
+#[derive(Default)]
+struct BoolWriter {
+	bits: Vec<bool>,
+}
+impl BitWrite for BoolWriter {
+	fn bits_written(&self) -> usize {
+		self.bits.len()
+	}
+
+	fn write_bit(&mut self, bit: bool) {
+		self.bits.push(bit);
+	}
+
+	fn write_uint<N>(&mut self, uint: N, bits: usize)
+	where
+		N: BitMemory + IsNumber + IsUnsigned,
+	{
+		let uint: u128 = uint.try_into().unwrap_or_else(|_| unreachable!());
+		for i in 0..bits {
+			self.bits.push(uint >> i & 1 == 1);
+		}
+	}
+}
+
+test!(
+	test_generic_backends_lsb,
+	test_generic_backends_msb,
+	bench_generic_backends_lsb,
+	bench_generic_backends_msb,
+	{
+		use crate::{
+			ArrayBitWriter, BitSliceReader, BitSliceWriter, CachedBitReader, StreamBitReader,
+			StreamBitWriter,
+		};
+
+		let expected = packet();
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		encode(&mut bitbuf, &expected);
+		let bits = BitWrite::bits_written(&bitbuf);
+		assert_eq!(BitWrite::bits_left(&bitbuf), None);
+		let bytes = bitbuf.into_bytes();
+
+		let mut buf = [0; 64];
+		let mut bitbuf = BitSliceWriter::<Endian>::from_bytes(&mut buf);
+		encode(&mut bitbuf, &expected);
+		assert_eq!(bitbuf.written_bytes(), &bytes[..]);
+		assert_eq!(BitWrite::bits_left(&bitbuf), Some(64 * 8 - bits));
+
+		let mut bitbuf = ArrayBitWriter::<Endian, 64>::new();
+		encode(&mut bitbuf, &expected);
+		assert_eq!(bitbuf.as_bytes(), &bytes[..]);
+		assert_eq!(BitWrite::bits_left(&bitbuf), Some(64 * 8 - bits));
+
+		let mut bitbuf = StreamBitWriter::<_, Endian>::new(Vec::new());
+		encode(&mut bitbuf, &expected);
+		assert_eq!(BitWrite::bytes_written(&bitbuf), bytes.len());
+		assert_eq!(BitWrite::bits_left(&bitbuf), None);
+		assert_eq!(bitbuf.finish().unwrap(), bytes);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bytes.clone());
+		assert_eq!(decode(&mut bitbuf), Ok(packet()));
+		assert_eq!(BitRead::bits_read(&bitbuf), bits);
+
+		let mut bitbuf = BitSliceReader::<Endian>::from_bytes(&bytes);
+		assert_eq!(decode(&mut bitbuf), Ok(packet()));
+		assert_eq!(BitRead::bits_read(&bitbuf), bits);
+
+		let mut bitbuf = CachedBitReader::<Endian>::new(&bytes);
+		assert_eq!(decode(&mut bitbuf), Ok(packet()));
+		assert_eq!(BitRead::bits_read(&bitbuf), bits);
+
+		let mut bitbuf = StreamBitReader::<_, Endian>::with_capacity(Trickle(&bytes[..]), 1);
+		assert_eq!(decode(&mut bitbuf), Ok(packet()));
+		assert_eq!(BitRead::bits_read(&bitbuf), bits);
+		assert_eq!(BitRead::bits_left(&bitbuf), None);
+	}
+);
+
+test!(
+	test_generic_atomic_lsb,
+	test_generic_atomic_msb,
+	bench_generic_atomic_lsb,
+	bench_generic_atomic_msb,
+	{
+		use crate::{BitSliceReader, CachedBitReader, StreamBitReader};
+
+		// A packet cut short in its tags fails without moving any reader's cursor
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(false);
+		encode(&mut bitbuf, &packet());
+		let mut bytes = bitbuf.into_bytes();
+		bytes.truncate(bytes.len() - 16);
+
+		fn check<R: BitRead>(reader: &mut R) {
+			assert_eq!(reader.read_bit(), Ok(false));
+			assert!(matches!(decode(reader), Err(ReadError::OutOfBounds { .. })));
+			assert_eq!(reader.bits_read(), 1);
+			assert_eq!(reader.read_bit(), Ok(true));
+			assert_eq!(reader.read_uint::<u16>(10), Ok(0x3FF));
+		}
+		check(&mut BitVecReader::<Endian>::from_bytes(bytes.clone()));
+		check(&mut BitSliceReader::<Endian>::from_bytes(&bytes));
+		check(&mut CachedBitReader::<Endian>::new(&bytes));
+		// Refills during the failed decode must keep the bytes it started in
+		check(&mut StreamBitReader::<_, Endian>::with_capacity(
+			Trickle(&bytes[..]),
+			1,
+		));
+
+		// So does a skip or seek past the buffer while running atomically
+		let mut bitbuf = StreamBitReader::<_, Endian>::with_capacity(Cursor::new(&bytes[..]), 1);
+		assert_eq!(bitbuf.read_bit(), Ok(false));
+		let result: Result<(), ReadError> = BitRead::atomically(&mut bitbuf, |reader| {
+			reader.skip(20)?;
+			reader.seek_bits(60)?;
+			Err(ReadError::InvalidValue {
+				offset: 60,
+				value: 0,
+			})
+		});
+		assert!(result.is_err());
+		assert_eq!(BitRead::bits_read(&bitbuf), 1);
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+
+		// But seeking back before the buffer fails, rather than throw away the bits to roll back to
+		let mut bitbuf = StreamBitReader::<_, Endian>::with_capacity(Cursor::new(&bytes[..]), 1);
+		assert_eq!(bitbuf.read_bit(), Ok(false));
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.read_uint::<u16>(10), Ok(0x3FF));
+		assert_eq!(bitbuf.read_int::<i8>(5), Ok(-5));
+		let result: Result<(), ReadError> = BitRead::atomically(&mut bitbuf, |reader| {
+			reader.skip(4)?;
+			reader.seek_bits(0)
+		});
+		assert_eq!(
+			result,
+			Err(ReadError::Io {
+				offset: 21,
+				kind: io::ErrorKind::InvalidInput
+			})
+		);
+		assert_eq!(BitRead::bits_read(&bitbuf), 17);
+		assert_eq!(bitbuf.read_float(), Ok(1.25));
+	}
+);
+
+#[test]
+fn test_generic_defaults() {
+	use crate::{BitVecReader, BitVecWriter, Lsb0};
+
+	// The defaults built on `write_bit` and `write_uint` write the same bits as the crate's writers
+	let mut writer = BoolWriter::default();
+	encode(&mut writer, &packet());
+	writer.write_ubitvar(300);
+	writer.write_field_index(-1, Some(40), true);
+	writer.write_bit_coord_mp(-12.5, false, true);
+	writer.write_bit_vec3_normal([0.6, -0.8, 0.]);
+	writer.write_signed_varint32(-3);
+	writer.write_uints(&[1, 2, 3], 7);
+	writer.write_string("end");
+
+	let mut bitbuf = BitVecWriter::<Lsb0>::default();
+	encode(&mut bitbuf, &packet());
+	bitbuf.write_ubitvar(300);
+	bitbuf.write_field_index(-1, Some(40), true);
+	bitbuf.write_bit_coord_mp(-12.5, false, true);
+	bitbuf.write_bit_vec3_normal([0.6, -0.8, 0.]);
+	bitbuf.write_signed_varint32(-3);
+	bitbuf.write_uints(&[1, 2, 3], 7);
+	bitbuf.write_string("end");
+	let bitvec = bitbuf.into_bitvec();
+	assert_eq!(writer.bits, bitvec.iter().by_val().collect::<Vec<_>>());
+	assert_eq!(writer.bytes_written(), (bitvec.len() + 7) / 8);
+
+	// And the defaults built on `read_bit` and `read_uint` read them back the same way
+	let mut reader = BoolReader {
+		bits: writer.bits,
+		cursor: 0,
+	};
+	let mut bitbuf = BitVecReader::<Lsb0>::new(bitvec);
+	assert_eq!(decode(&mut reader), Ok(packet()));
+	assert_eq!(decode(&mut bitbuf), Ok(packet()));
+	assert_eq!(reader.read_ubitvar(), bitbuf.read_ubitvar());
+	assert_eq!(reader.read_field_index(-1, true), Ok(Some(40)));
+	bitbuf.read_field_index(-1, true).unwrap();
+	assert_eq!(
+		reader.read_bit_coord_mp(false, true),
+		bitbuf.read_bit_coord_mp(false, true)
+	);
+	assert_eq!(reader.read_bit_vec3_normal(), bitbuf.read_bit_vec3_normal());
+	assert_eq!(reader.read_signed_varint32(), Ok(-3));
+	bitbuf.read_signed_varint32().unwrap();
+	let mut uints = [0; 3];
+	assert_eq!(reader.read_uints_into(&mut uints, 7), Ok(()));
+	assert_eq!(uints, [1, 2, 3]);
+	assert_eq!(reader.read_string(3), Ok("end".to_string()));
+	assert_eq!(reader.bits_left(), Some(0));
+	assert_eq!(reader.bits_read(), bitbuf.bits_len());
+
+	// Errors carry the offset the read started at
+	let mut reader = BoolReader {
+		bits: vec![true; 80],
+		cursor: 3,
+	};
+	assert_eq!(
+		reader.read_varint32(),
+		Err(ReadError::LengthLimitExceeded {
+			offset: 3,
+			limit: crate::MAX_VARINT32_BYTES
+		})
+	);
+	reader.cursor = 0;
+	assert!(matches!(
+		reader.read_string(2),
+		Err(ReadError::InvalidUtf8 { offset: 0, .. })
+	));
+}
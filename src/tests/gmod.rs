@@ -0,0 +1,120 @@
+use crate::{
+	gmod::{Color, NetRead, NetWrite, Value},
+	BitVecReader, BitVecWriter, Lsb0, ReadError,
+};
+
+#[test]
+fn test_gmod_table_fixture() {
+	// net.WriteTable({ [1] = "a" })
+	let fixture = [3, 0, 0, 0, 0, 0, 0, 0xF0, 0x3F, 4, b'a', 0, 0];
+	let table = vec![(Value::Number(1.), Value::String("a".to_string()))];
+
+	let mut bitbuf = BitVecWriter::<Lsb0>::default();
+	bitbuf.write_table(&table);
+	assert_eq!(bitbuf.into_bytes(), fixture);
+
+	let mut bitbuf = BitVecReader::<Lsb0>::from_bytes(fixture.to_vec());
+	assert_eq!(bitbuf.read_table(), Ok(table));
+	assert_eq!(bitbuf.bits_left(), 0);
+}
+
+#[test]
+fn test_gmod_vector_fixture() {
+	// net.WriteBool(true) net.WriteVector(Vector(1.5, 0, 0)) net.WriteEntity(Entity(258))
+	let fixture = [0b0011_0011, 0, 0, 0b0000_1010, 0b0000_0100, 0];
+
+	let mut bitbuf = BitVecWriter::<Lsb0>::default();
+	bitbuf.write_bit(true);
+	bitbuf.write_vector([1.5, 0., 0.]);
+	bitbuf.write_entity(258);
+	assert_eq!(bitbuf.bits_written(), 1 + 25 + 16);
+	assert_eq!(bitbuf.into_bytes(), fixture);
+
+	let mut bitbuf = BitVecReader::<Lsb0>::from_bytes(fixture.to_vec());
+	assert_eq!(bitbuf.read_bit(), Ok(true));
+	assert_eq!(bitbuf.read_vector(), Ok([1.5, 0., 0.]));
+	assert_eq!(bitbuf.read_entity(), Ok(258));
+}
+
+#[test]
+fn test_gmod_color_fixture() {
+	let color = Color {
+		r: 255,
+		g: 128,
+		b: 0,
+		a: 64,
+	};
+
+	let mut bitbuf = BitVecWriter::<Lsb0>::default();
+	bitbuf.write_color(color, true);
+	bitbuf.write_color(color, false);
+	assert_eq!(bitbuf.into_bytes(), [255, 128, 0, 64, 255, 128, 0]);
+
+	let mut bitbuf = BitVecReader::<Lsb0>::from_bytes(vec![255, 128, 0, 64, 255, 128, 0]);
+	assert_eq!(bitbuf.read_color(true), Ok(color));
+	assert_eq!(bitbuf.read_color(false), Ok(Color { a: 255, ..color }));
+}
+
+#[test]
+fn test_gmod_nested_table() {
+	let table = vec![
+		(
+			Value::String("nested".to_string()),
+			Value::Table(vec![
+				(Value::Number(1.), Value::Bool(true)),
+				(Value::Number(2.), Value::Entity(0)),
+				(
+					Value::Bool(false),
+					Value::Table(vec![(
+						Value::Angle([90., 0., -45.5]),
+						Value::Vector([0., 1., 2.]),
+					)]),
+				),
+			]),
+		),
+		(Value::Number(-1.5), Value::Matrix([1.; 16])),
+		(
+			Value::Color(Color {
+				r: 1,
+				g: 2,
+				b: 3,
+				a: 4,
+			}),
+			Value::Table(vec![]),
+		),
+	];
+
+	let mut bitbuf = BitVecWriter::<Lsb0>::default();
+	bitbuf.write_bit(true);
+	bitbuf.write_table(&table);
+	bitbuf.write_type(None);
+	bitbuf.write_normal([0., 0., -1.]);
+
+	let mut bitbuf = BitVecReader::<Lsb0>::from_bytes(bitbuf.into_bytes());
+	assert_eq!(bitbuf.read_bit(), Ok(true));
+	assert_eq!(bitbuf.read_table(), Ok(table));
+	assert_eq!(bitbuf.read_type(), Ok(None));
+	assert_eq!(bitbuf.read_normal(), Ok([0., 0., -1.]));
+}
+
+#[test]
+fn test_gmod_invalid_table() {
+	// A key of unknown type 2
+	let mut bitbuf = BitVecReader::<Lsb0>::from_bytes(vec![4, b'a', 0, 2]);
+	assert_eq!(
+		bitbuf.read_table(),
+		Err(ReadError::InvalidValue {
+			offset: 24,
+			value: 2
+		})
+	);
+	assert_eq!(bitbuf.cursor, 0);
+
+	// Tables nested deeper than Lua allows
+	let mut bitbuf = BitVecReader::<Lsb0>::from_bytes(vec![5; 300]);
+	assert!(matches!(
+		bitbuf.read_table(),
+		Err(ReadError::LengthLimitExceeded { limit: 200, .. })
+	));
+	assert_eq!(bitbuf.cursor, 0);
+}
@@ -0,0 +1,138 @@
+use crate::IncrementalReader;
+
+test!(
+	test_incremental_reader_lsb,
+	test_incremental_reader_msb,
+	bench_incremental_reader_lsb,
+	bench_incremental_reader_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_uint(5_u8, 3);
+		bitbuf.write_string_nul("Hello, world!");
+		bitbuf.write_varint32(300);
+		bitbuf.write_int(-69_i16, 14);
+		let bytes = bitbuf.into_bytes();
+
+		let mut bitbuf = IncrementalReader::<Endian>::new();
+		bitbuf.feed(&bytes[..1]);
+		assert_eq!(bitbuf.decode(|reader| reader.read_uint::<u8>(3)), Ok(5));
+		assert_eq!(
+			bitbuf.decode(|reader| reader.read_uint::<u16>(16)),
+			Err(ReadError::Incomplete {
+				offset: 3,
+				needed_bits: 11
+			})
+		);
+
+		// Feed the rest a byte at a time, retrying the message after each one
+		let mut decoded = Vec::new();
+		for byte in &bytes[1..] {
+			bitbuf.feed([*byte]);
+			if !decoded.is_empty() {
+				continue;
+			}
+			match bitbuf.decode(|reader| {
+				let string = reader.read_nul_string()?;
+				let varint = reader.read_varint32()?;
+				Ok((string, varint))
+			}) {
+				Ok(message) => decoded.push(message),
+				Err(ReadError::Incomplete { .. }) => assert_eq!(bitbuf.bits_read(), 3),
+				Err(error) => panic!("{}", error),
+			}
+		}
+		assert_eq!(decoded, vec![("Hello, world!".to_string(), 300)]);
+		assert_eq!(bitbuf.decode(|reader| reader.read_int::<i16>(14)), Ok(-69));
+		assert_eq!(bitbuf.bits_read(), 3 + 14 * 8 + 16 + 14);
+		assert!(bitbuf.bits_buffered() < 8);
+	}
+);
+
+test!(
+	test_incremental_reader_checkpoint_lsb,
+	test_incremental_reader_checkpoint_msb,
+	bench_incremental_reader_checkpoint_lsb,
+	bench_incremental_reader_checkpoint_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_byte(0xAB);
+		bitbuf.write_uint(0x1234_u16, 16);
+		bitbuf.write_byte(0xCD);
+		let bytes = bitbuf.into_bytes();
+
+		let mut bitbuf = IncrementalReader::<Endian>::new();
+		bitbuf.feed(&bytes[..2]);
+		bitbuf.checkpoint();
+		assert_eq!(bitbuf.decode(|reader| reader.read_byte()), Ok(0xAB));
+		assert!(bitbuf.decode(|reader| reader.read_uint::<u16>(16)).is_err());
+
+		// The byte before the checkpoint is kept while more are fed
+		bitbuf.feed(&bytes[2..]);
+		bitbuf.rollback();
+		assert_eq!(bitbuf.bits_read(), 0);
+		assert_eq!(bitbuf.decode(|reader| reader.read_byte()), Ok(0xAB));
+		assert_eq!(
+			bitbuf.decode(|reader| reader.read_uint::<u16>(16)),
+			Ok(0x1234)
+		);
+		bitbuf.rollback();
+		assert_eq!(bitbuf.bits_read(), 24);
+
+		// Other errors are passed through, with offsets counted from the first byte fed
+		bitbuf.feed([]);
+		assert_eq!(
+			bitbuf.decode(|reader| reader.read_uint::<u8>(9)),
+			Err(ReadError::OutOfRange {
+				offset: 24,
+				bits: 9,
+				max: 8
+			})
+		);
+		assert_eq!(bitbuf.decode(|reader| reader.read_byte()), Ok(0xCD));
+	}
+);
+
+test!(
+	test_incremental_reader_max_bits_lsb,
+	test_incremental_reader_max_bits_msb,
+	bench_incremental_reader_max_bits_lsb,
+	bench_incremental_reader_max_bits_msb,
+	{
+		// A length no buffer could ever hold isn't incomplete
+		let mut bitbuf = IncrementalReader::<Endian>::new();
+		bitbuf.feed([0xFF]);
+		assert_eq!(bitbuf.decode(|reader| reader.read_uint::<u8>(3)), Ok(7));
+		assert_eq!(
+			bitbuf.decode(|reader| reader.read_string(usize::MAX)),
+			Err(ReadError::OutOfBounds {
+				offset: 3,
+				requested: usize::MAX,
+				available: 5
+			})
+		);
+
+		let mut bitbuf = IncrementalReader::<Endian>::with_max_bits(16);
+		assert_eq!(bitbuf.max_bits(), 16);
+		bitbuf.feed([0xFF]);
+		assert_eq!(bitbuf.decode(|reader| reader.read_uint::<u8>(3)), Ok(7));
+		assert_eq!(
+			bitbuf.decode(|reader| reader.read_uint::<u16>(16)),
+			Err(ReadError::Incomplete {
+				offset: 3,
+				needed_bits: 11
+			})
+		);
+		assert_eq!(
+			bitbuf.decode(|reader| {
+				reader.read_uint::<u8>(5)?;
+				reader.read_uint::<u16>(12)
+			}),
+			Err(ReadError::OutOfBounds {
+				offset: 8,
+				requested: 12,
+				available: 0
+			})
+		);
+		assert_eq!(bitbuf.bits_read(), 3);
+	}
+);
@@ -3,7 +3,7 @@ macro_rules! test {
 		#[test]
 		fn $test_lsb() {
 			#[allow(unused)]
-			use crate::{BitVecReader, BitVecWriter};
+			use crate::{BitVecReader, BitVecWriter, ReadError};
 			type Endian = bitvec::prelude::Lsb0;
 			$code
 		}
@@ -11,7 +11,7 @@ macro_rules! test {
 		#[test]
 		fn $test_msb() {
 			#[allow(unused)]
-			use crate::{BitVecReader, BitVecWriter};
+			use crate::{BitVecReader, BitVecWriter, ReadError};
 			type Endian = bitvec::prelude::Msb0;
 			$code
 		}
@@ -20,7 +20,7 @@ macro_rules! test {
 		#[bench]
 		fn $bench_lsb(b: &mut test::Bencher) {
 			#[allow(unused)]
-			use crate::{BitVecReader, BitVecWriter};
+			use crate::{BitVecReader, BitVecWriter, ReadError};
 			type Endian = bitvec::prelude::Lsb0;
 			b.iter(|| $code);
 		}
@@ -29,7 +29,7 @@ macro_rules! test {
 		#[bench]
 		fn $bench_msb(b: &mut test::Bencher) {
 			#[allow(unused)]
-			use crate::{BitVecReader, BitVecWriter};
+			use crate::{BitVecReader, BitVecWriter, ReadError};
 			type Endian = bitvec::prelude::Msb0;
 			b.iter(|| $code);
 		}
@@ -39,7 +39,7 @@ macro_rules! test {
 		#[test]
 		fn $test_lsb() {
 			#[allow(unused)]
-			use crate::{BitVecReader, BitVecWriter};
+			use crate::{BitVecReader, BitVecWriter, ReadError};
 			type Endian = bitvec::prelude::Lsb0;
 			let $result_var_name = $lsb_result;
 			$code
@@ -48,7 +48,7 @@ macro_rules! test {
 		#[test]
 		fn $test_msb() {
 			#[allow(unused)]
-			use crate::{BitVecReader, BitVecWriter};
+			use crate::{BitVecReader, BitVecWriter, ReadError};
 			type Endian = bitvec::prelude::Msb0;
 			let $result_var_name = $msb_result;
 			$code
@@ -58,7 +58,7 @@ macro_rules! test {
 		#[bench]
 		fn $bench_lsb(b: &mut test::Bencher) {
 			#[allow(unused)]
-			use crate::{BitVecReader, BitVecWriter};
+			use crate::{BitVecReader, BitVecWriter, ReadError};
 			type Endian = bitvec::prelude::Lsb0;
 			let $result_var_name = $lsb_result;
 			b.iter(|| $code);
@@ -68,7 +68,7 @@ macro_rules! test {
 		#[bench]
 		fn $bench_msb(b: &mut test::Bencher) {
 			#[allow(unused)]
-			use crate::{BitVecReader, BitVecWriter};
+			use crate::{BitVecReader, BitVecWriter, ReadError};
 			type Endian = bitvec::prelude::Msb0;
 			let $result_var_name = $msb_result;
 			b.iter(|| $code);
@@ -76,7 +76,17 @@ macro_rules! test {
 	};
 }
 
+mod cached;
+#[cfg(feature = "derive")]
+mod derive;
+mod encode;
+mod generic;
+mod gmod;
+mod incremental;
 mod read;
+#[cfg(feature = "serde")]
+mod serde;
+mod stream;
 mod write;
 
 #[test]
@@ -88,10 +98,9 @@ fn test_empty_bitbuf() {
 	assert_eq!(bitbuf.into_bytes(), &[]);
 }
 
-
 #[test]
 fn test_set_bits() {
-	use crate::{BitVecWriter, BitVecReader};
+	use crate::{BitVecReader, BitVecWriter};
 	use bitvec::prelude::Lsb0;
 	use bitvec::view::BitView;
 
@@ -100,5 +109,19 @@ fn test_set_bits() {
 	bitbuf.set_bits(0, 420_u32.view_bits());
 
 	let mut bitbuf = BitVecReader::<Lsb0>::from_bytes(bitbuf.into_bytes());
-	assert_eq!(bitbuf.read_uint(32), Some(420_u32));
+	assert_eq!(bitbuf.read_uint(32), Ok(420_u32));
+}
+
+#[test]
+fn test_writer_from_bytes() {
+	use crate::BitVecWriter;
+	use bitvec::prelude::Lsb0;
+
+	let mut bitbuf = BitVecWriter::<Lsb0>::from_byte_slice(b"Hello");
+	bitbuf.write_string(", world!");
+	assert_eq!(bitbuf.into_bytes(), b"Hello, world!");
+
+	let mut bitbuf = BitVecWriter::<Lsb0>::from_bytes(b"Hello".to_vec());
+	bitbuf.write_string(", world!");
+	assert_eq!(bitbuf.into_bytes(), b"Hello, world!");
 }
@@ -10,9 +10,9 @@ test!(
 		bitbuf.write_bit(true);
 
 		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
-		assert_eq!(bitbuf.read_bit(), Some(true));
-		assert_eq!(bitbuf.read_bit(), Some(false));
-		assert_eq!(bitbuf.read_bit(), Some(true));
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.read_bit(), Ok(false));
+		assert_eq!(bitbuf.read_bit(), Ok(true));
 	}
 );
 
@@ -27,9 +27,16 @@ test!(
 		bitbuf.write_byte(50);
 
 		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
-		assert_eq!(bitbuf.read_byte(), Some(69));
-		assert_eq!(bitbuf.read_byte(), Some(50));
-		assert_eq!(bitbuf.read_byte(), None);
+		assert_eq!(bitbuf.read_byte(), Ok(69));
+		assert_eq!(bitbuf.read_byte(), Ok(50));
+		assert_eq!(
+			bitbuf.read_byte(),
+			Err(ReadError::OutOfBounds {
+				offset: 16,
+				requested: 8,
+				available: 0
+			})
+		);
 	}
 );
 
@@ -45,10 +52,17 @@ test!(
 		bitbuf.write_uint(u16::MAX, 16);
 
 		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
-		assert_eq!(bitbuf.read_uint(12), Some(69_u16));
-		assert_eq!(bitbuf.read_uint(15), Some(12_u16));
-		assert_eq!(bitbuf.read_uint(16), Some(u16::MAX));
-		assert_eq!(bitbuf.read_uint(16), None::<u16>);
+		assert_eq!(bitbuf.read_uint(12), Ok(69_u16));
+		assert_eq!(bitbuf.read_uint(15), Ok(12_u16));
+		assert_eq!(bitbuf.read_uint(16), Ok(u16::MAX));
+		assert_eq!(
+			bitbuf.read_uint::<u16>(16),
+			Err(ReadError::OutOfBounds {
+				offset: 43,
+				requested: 16,
+				available: 5
+			})
+		);
 	}
 );
 
@@ -62,12 +76,25 @@ test!(
 		bitbuf.write_int(-69_i16, 14);
 		bitbuf.write_int(-12_i16, 15);
 		bitbuf.write_int(i16::MIN, 16);
+		bitbuf.write_int(69_i16, 14);
+		bitbuf.write_int(-1_i8, 1);
+		bitbuf.write_int(0_i8, 1);
 
 		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
-		assert_eq!(bitbuf.read_int(14), Some(-69_i16));
-		assert_eq!(bitbuf.read_int(15), Some(-12_i16));
-		assert_eq!(bitbuf.read_int(16), Some(i16::MIN));
-		assert_eq!(bitbuf.read_int(16), None::<i16>);
+		assert_eq!(bitbuf.read_int(14), Ok(-69_i16));
+		assert_eq!(bitbuf.read_int(15), Ok(-12_i16));
+		assert_eq!(bitbuf.read_int(16), Ok(i16::MIN));
+		assert_eq!(bitbuf.read_int(14), Ok(69_i16));
+		assert_eq!(bitbuf.read_int(1), Ok(-1_i8));
+		assert_eq!(bitbuf.read_int(1), Ok(0_i8));
+		assert_eq!(
+			bitbuf.read_int::<i16>(16),
+			Err(ReadError::OutOfBounds {
+				offset: 61,
+				requested: 16,
+				available: 3
+			})
+		);
 	}
 );
 
@@ -84,11 +111,18 @@ test!(
 		bitbuf.write_float(f32::MIN);
 
 		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
-		assert_eq!(bitbuf.read_float(), Some(69.69_f32));
-		assert_eq!(bitbuf.read_float(), Some(0.01_f32));
-		assert_eq!(bitbuf.read_float(), Some(f32::MAX));
-		assert_eq!(bitbuf.read_float(), Some(f32::MIN));
-		assert_eq!(bitbuf.read_float(), None::<f32>);
+		assert_eq!(bitbuf.read_float(), Ok(69.69_f32));
+		assert_eq!(bitbuf.read_float(), Ok(0.01_f32));
+		assert_eq!(bitbuf.read_float(), Ok(f32::MAX));
+		assert_eq!(bitbuf.read_float(), Ok(f32::MIN));
+		assert_eq!(
+			bitbuf.read_float(),
+			Err(ReadError::OutOfBounds {
+				offset: 128,
+				requested: 32,
+				available: 0
+			})
+		);
 	}
 );
 
@@ -105,11 +139,18 @@ test!(
 		bitbuf.write_double(f64::MIN);
 
 		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
-		assert_eq!(bitbuf.read_double(), Some(69.69_f64));
-		assert_eq!(bitbuf.read_double(), Some(0.01_f64));
-		assert_eq!(bitbuf.read_double(), Some(f64::MAX));
-		assert_eq!(bitbuf.read_double(), Some(f64::MIN));
-		assert_eq!(bitbuf.read_double(), None::<f64>);
+		assert_eq!(bitbuf.read_double(), Ok(69.69_f64));
+		assert_eq!(bitbuf.read_double(), Ok(0.01_f64));
+		assert_eq!(bitbuf.read_double(), Ok(f64::MAX));
+		assert_eq!(bitbuf.read_double(), Ok(f64::MIN));
+		assert_eq!(
+			bitbuf.read_double(),
+			Err(ReadError::OutOfBounds {
+				offset: 256,
+				requested: 64,
+				available: 0
+			})
+		);
 	}
 );
 
@@ -131,28 +172,25 @@ test!(
 		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
 		assert_eq!(
 			bitbuf.read_string("Hello, world!".len()),
-			Some(Ok("Hello, world!".to_string()))
+			Ok("Hello, world!".to_string())
 		);
 		assert_eq!(
 			unsafe { bitbuf.read_string_unchecked("Hello, world!".len()) },
-			Some("Hello, world!".to_string())
+			Ok("Hello, world!".to_string())
 		);
 		assert_eq!(
 			bitbuf.read_string_lossy("Hello, world!".len()),
-			Some("Hello, world!".to_string())
+			Ok("Hello, world!".to_string())
 		);
 
-		assert_eq!(
-			bitbuf.read_nul_string(),
-			Some(Ok("Hello, world!".to_string()))
-		);
+		assert_eq!(bitbuf.read_nul_string(), Ok("Hello, world!".to_string()));
 		assert_eq!(
 			unsafe { bitbuf.read_nul_string_unchecked() },
-			Some("Hello, world!".to_string())
+			Ok("Hello, world!".to_string())
 		);
 		assert_eq!(
 			bitbuf.read_nul_string_lossy(),
-			Some("Hello, world!".to_string())
+			Ok("Hello, world!".to_string())
 		);
 	}
 );
@@ -169,7 +207,27 @@ test!(
 		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
 		assert_eq!(
 			bitbuf.read_bytes(b"Hello, world!".len()),
-			Some(b"Hello, world!".to_vec())
+			Ok(b"Hello, world!".to_vec())
+		);
+
+		// Lengths too long to count in bits are out of bounds, wherever the cursor is
+		bitbuf.cursor = 0;
+		assert_eq!(
+			bitbuf.read_bytes(usize::MAX),
+			Err(ReadError::OutOfBounds {
+				offset: 0,
+				requested: usize::MAX,
+				available: 104
+			})
+		);
+		bitbuf.cursor = 3;
+		assert_eq!(
+			bitbuf.read_string(usize::MAX / 8),
+			Err(ReadError::OutOfBounds {
+				offset: 3,
+				requested: usize::MAX / 8 * 8,
+				available: 101
+			})
 		);
 	}
 );
@@ -185,7 +243,696 @@ test!(
 		bitbuf.write_bit(true);
 
 		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
-		assert_eq!(bitbuf.read_uint(16), Some(162_u16));
-		assert_eq!(bitbuf.read_bit(), Some(true));
+		assert_eq!(bitbuf.read_uint(16), Ok(162_u16));
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+	}
+);
+
+test!(
+	test_read_errors_lsb,
+	test_read_errors_msb,
+	bench_read_errors_lsb,
+	bench_read_errors_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		bitbuf.write_bytes([0xC3, 0x28, 0x00]);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(
+			bitbuf.read_uint::<u8>(9),
+			Err(ReadError::OutOfRange {
+				offset: 0,
+				bits: 9,
+				max: 8
+			})
+		);
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+
+		let err = bitbuf.read_string(2).unwrap_err();
+		assert!(matches!(err, ReadError::InvalidUtf8 { offset: 1, .. }));
+		assert_eq!(err.offset(), 1);
+	}
+);
+
+test!(
+	test_read_truncated_lsb,
+	test_read_truncated_msb,
+	bench_read_truncated_lsb,
+	bench_read_truncated_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		bitbuf.write_bytes(b"ab");
+
+		// 24 bits in total, of which the last 7 are padding
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+
+		macro_rules! assert_truncated {
+			($read:expr) => {
+				assert!(matches!(
+					$read,
+					Err(ReadError::OutOfBounds {
+						offset: 1,
+						available: 23,
+						..
+					})
+				));
+				assert_eq!(bitbuf.cursor, 1);
+				assert_eq!(bitbuf.bits_left(), 23);
+			};
+		}
+		assert_truncated!(bitbuf.read_bytes(3));
+		assert_truncated!(bitbuf.read_uint::<u32>(24));
+		assert_truncated!(bitbuf.read_int::<i32>(24));
+		assert_truncated!(bitbuf.read_float());
+		assert_truncated!(bitbuf.read_double());
+		assert_truncated!(bitbuf.read_string(3));
+		assert_truncated!(unsafe { bitbuf.read_string_unchecked(3) });
+		assert_truncated!(bitbuf.read_string_lossy(3));
+		assert_truncated!(bitbuf.read_nul_string());
+		assert_truncated!(unsafe { bitbuf.read_nul_string_unchecked() });
+		assert_truncated!(bitbuf.read_nul_string_lossy());
+
+		assert_eq!(bitbuf.read_bytes(2), Ok(b"ab".to_vec()));
+		assert!(bitbuf.read_byte().is_err());
+		assert_eq!(bitbuf.cursor, 17);
+		assert_eq!(bitbuf.read_uint(7), Ok(0_u8));
+		assert!(bitbuf.read_bit().is_err());
+		assert_eq!(bitbuf.cursor, 24);
+		assert_eq!(bitbuf.bits_left(), 0);
+	}
+);
+
+test!(
+	test_read_invalid_utf8_lsb,
+	test_read_invalid_utf8_msb,
+	bench_read_invalid_utf8_lsb,
+	bench_read_invalid_utf8_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bytes([0xC3, 0x28, 0x00]);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert!(matches!(
+			bitbuf.read_string(2),
+			Err(ReadError::InvalidUtf8 { offset: 0, .. })
+		));
+		assert_eq!(bitbuf.cursor, 0);
+		assert!(matches!(
+			bitbuf.read_nul_string(),
+			Err(ReadError::InvalidUtf8 { offset: 0, .. })
+		));
+		assert_eq!(bitbuf.cursor, 0);
+		assert_eq!(bitbuf.read_nul_string_lossy(), Ok("\u{FFFD}(".to_string()));
+		assert_eq!(bitbuf.cursor, 24);
+	}
+);
+
+test!(
+	test_read_varint_lsb,
+	test_read_varint_msb,
+	bench_read_varint_lsb,
+	bench_read_varint_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		bitbuf.write_varint32(0);
+		bitbuf.write_varint32(300);
+		bitbuf.write_varint32(u32::MAX);
+		bitbuf.write_varint64(u64::MAX);
+		bitbuf.write_signed_varint32(-1);
+		bitbuf.write_signed_varint32(i32::MIN);
+		bitbuf.write_signed_varint64(i64::MAX);
+		bitbuf.write_signed_varint64(-300);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.read_varint32(), Ok(0));
+		assert_eq!(bitbuf.read_varint32(), Ok(300));
+		assert_eq!(bitbuf.read_varint32(), Ok(u32::MAX));
+		assert_eq!(bitbuf.read_varint64(), Ok(u64::MAX));
+		assert_eq!(bitbuf.read_signed_varint32(), Ok(-1));
+		assert_eq!(bitbuf.read_signed_varint32(), Ok(i32::MIN));
+		assert_eq!(bitbuf.read_signed_varint64(), Ok(i64::MAX));
+		assert_eq!(bitbuf.read_signed_varint64(), Ok(-300));
+		assert_eq!(bitbuf.bits_left(), 7);
+	}
+);
+
+test!(
+	test_read_varint_malformed_lsb,
+	test_read_varint_malformed_msb,
+	bench_read_varint_malformed_lsb,
+	bench_read_varint_malformed_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bytes([0xFF; 6]);
+		bitbuf.write_byte(0x01);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(
+			bitbuf.read_varint32(),
+			Err(ReadError::LengthLimitExceeded {
+				offset: 0,
+				limit: 5
+			})
+		);
+		assert_eq!(bitbuf.cursor, 0);
+
+		bitbuf.advance(16);
+		assert_eq!(bitbuf.read_varint64(), Ok(0x1FFF_FFFF));
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(vec![0x80, 0x80]);
+		assert_eq!(
+			bitbuf.read_varint32(),
+			Err(ReadError::OutOfBounds {
+				offset: 0,
+				requested: 24,
+				available: 16
+			})
+		);
+		assert_eq!(bitbuf.cursor, 0);
+	}
+);
+
+test!(
+	test_read_ubitvar_lsb,
+	test_read_ubitvar_msb,
+	bench_read_ubitvar_lsb,
+	bench_read_ubitvar_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		for uint in [0, 15, 16, 255, 256, 4095, 4096, u32::MAX] {
+			bitbuf.write_ubitvar(uint);
+		}
+		assert_eq!(bitbuf.bits_written(), 1 + 6 * 2 + 10 * 2 + 14 * 2 + 34 * 2);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		for uint in [0, 15, 16, 255, 256, 4095, 4096, u32::MAX] {
+			assert_eq!(bitbuf.read_ubitvar(), Ok(uint));
+		}
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(vec![0xFF]);
+		assert!(matches!(
+			bitbuf.read_ubitvar(),
+			Err(ReadError::OutOfBounds {
+				offset: 2,
+				requested: 32,
+				available: 6
+			})
+		));
+		assert_eq!(bitbuf.cursor, 0);
+	}
+);
+
+test!(
+	test_read_field_index_lsb,
+	test_read_field_index_msb,
+	bench_read_field_index_lsb,
+	bench_read_field_index_msb,
+	{
+		let indices = [0, 1, 5, 13, 50, 200, 800, 4800];
+		for new_way in [false, true] {
+			let mut bitbuf = BitVecWriter::<Endian>::default();
+			let mut last_index = -1;
+			for index in indices {
+				bitbuf.write_field_index(last_index, Some(index), new_way);
+				last_index = index;
+			}
+			bitbuf.write_field_index(last_index, None, new_way);
+
+			let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+			let mut last_index = -1;
+			for index in indices {
+				assert_eq!(
+					bitbuf.read_field_index(last_index, new_way),
+					Ok(Some(index))
+				);
+				last_index = index;
+			}
+			assert_eq!(bitbuf.read_field_index(last_index, new_way), Ok(None));
+		}
+
+		// An index past `i32::MAX` is invalid, in the short form and the long one
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_field_index(0, Some(1), true);
+		bitbuf.write_field_index(0, Some(50), false);
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(
+			bitbuf.read_field_index(i32::MAX, true),
+			Err(ReadError::InvalidValue {
+				offset: 0,
+				value: 0
+			})
+		);
+		assert_eq!(bitbuf.cursor, 0);
+		assert_eq!(bitbuf.read_field_index(0, true), Ok(Some(1)));
+		assert_eq!(
+			bitbuf.read_field_index(i32::MAX, false),
+			Err(ReadError::InvalidValue {
+				offset: 1,
+				value: 49
+			})
+		);
+		assert_eq!(bitbuf.cursor, 1);
+		assert_eq!(bitbuf.read_field_index(0, false), Ok(Some(50)));
+	}
+);
+
+test!(
+	test_read_bit_coord_lsb,
+	test_read_bit_coord_msb,
+	bench_read_bit_coord_lsb,
+	bench_read_bit_coord_msb,
+	{
+		let coords = [
+			(0.0, 0.0),
+			(1.5, 1.5),
+			(-1000.25, -1000.25),
+			(0.03125, 0.03125),
+			(16384.0 + 31.0 / 32.0, 16384.0 + 31.0 / 32.0),
+			(1.3, 1.28125),
+			(-0.01, 0.0),
+		];
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		for (coord, _) in coords {
+			bitbuf.write_bit_coord(coord);
+		}
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		for (_, coord) in coords {
+			assert_eq!(bitbuf.read_bit_coord(), Ok(coord));
+		}
+	}
+);
+
+test!(
+	test_read_bit_coord_mp_lsb,
+	test_read_bit_coord_mp_msb,
+	bench_read_bit_coord_mp_lsb,
+	bench_read_bit_coord_mp_msb,
+	{
+		// (coord, integral, low precision, default)
+		let coords = [
+			(1.5, [1.0, 1.5, 1.5]),
+			(-3000.75, [-3000.0, -3000.75, -3000.75]),
+			(0.3, [0.0, 0.25, 0.28125]),
+			(-0.3, [0.0, -0.25, -0.28125]),
+		];
+		let modes = [(true, false), (false, true), (false, false)];
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		for (coord, _) in coords {
+			for (integral, low_precision) in modes {
+				bitbuf.write_bit_coord_mp(coord, integral, low_precision);
+			}
+		}
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		for (_, expected) in coords {
+			for ((integral, low_precision), expected) in modes.iter().zip(expected) {
+				assert_eq!(
+					bitbuf.read_bit_coord_mp(*integral, *low_precision),
+					Ok(expected)
+				);
+			}
+		}
+	}
+);
+
+test!(
+	test_read_bit_normal_lsb,
+	test_read_bit_normal_msb,
+	bench_read_bit_normal_lsb,
+	bench_read_bit_normal_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit_normal(0.5);
+		bitbuf.write_bit_normal(-1.0);
+		bitbuf.write_bit_normal(2.0);
+		bitbuf.write_bit_vec3_normal([0.6, 0.0, -0.8]);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read_bit_normal(), Ok((1023.0 / 2047.0_f64) as f32));
+		assert_eq!(bitbuf.read_bit_normal(), Ok(-1.0));
+		assert_eq!(bitbuf.read_bit_normal(), Ok(1.0));
+
+		let x = (1228.0 / 2047.0_f64) as f32;
+		assert_eq!(
+			bitbuf.read_bit_vec3_normal(),
+			Ok([x, 0.0, -(1.0 - x * x).sqrt()])
+		);
+	}
+);
+
+test!(
+	test_read_bit_angle_lsb,
+	test_read_bit_angle_msb,
+	bench_read_bit_angle_lsb,
+	bench_read_bit_angle_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit_angle(90.0, 8);
+		bitbuf.write_bit_angle(-90.0, 8);
+		bitbuf.write_bit_angle(45.5, 16);
+		bitbuf.write_bit_angle(360.0, 7);
+		assert_eq!(bitbuf.bits_written(), 8 + 8 + 16 + 7);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(
+			bitbuf.read_bit_angle(64),
+			Err(ReadError::OutOfRange {
+				offset: 0,
+				bits: 64,
+				max: 32
+			})
+		);
+		assert_eq!(
+			bitbuf.read_bit_angle(0),
+			Err(ReadError::OutOfRange {
+				offset: 0,
+				bits: 0,
+				max: 32
+			})
+		);
+		assert_eq!(bitbuf.cursor, 0);
+		assert_eq!(bitbuf.read_bit_angle(8), Ok(90.0));
+		assert_eq!(bitbuf.read_bit_angle(8), Ok(270.0));
+		assert_eq!(
+			bitbuf.read_bit_angle(16),
+			Ok((8283.0 * (360.0 / 65536.0_f64)) as f32)
+		);
+		assert_eq!(bitbuf.read_bit_angle(7), Ok(0.0));
+	}
+);
+
+test!(
+	test_read_bit_vec3_coord_lsb,
+	test_read_bit_vec3_coord_msb,
+	bench_read_bit_vec3_coord_lsb,
+	bench_read_bit_vec3_coord_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit_vec3_coord([1.5, 0.0, -2.25]);
+		bitbuf.write_bit_vec3_coord([0.0, 0.0, 0.0]);
+		assert_eq!(bitbuf.bits_written(), 3 + 22 + 22 + 3);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read_bit_vec3_coord(), Ok([1.5, 0.0, -2.25]));
+		assert_eq!(bitbuf.read_bit_vec3_coord(), Ok([0.0, 0.0, 0.0]));
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(vec![0xFF]);
+		assert!(bitbuf.read_bit_vec3_coord().is_err());
+		assert_eq!(bitbuf.cursor, 0);
+	}
+);
+
+test!(
+	test_slice_reader_lsb,
+	test_slice_reader_msb,
+	bench_slice_reader_lsb,
+	bench_slice_reader_msb,
+	{
+		use crate::BitSliceReader;
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		bitbuf.write_int(-69_i16, 14);
+		bitbuf.write_varint32(300);
+		bitbuf.write_string_nul("Hello");
+		let bytes = bitbuf.into_bytes();
+
+		let mut bitbuf = BitSliceReader::<Endian>::from_bytes(&bytes);
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.read_int::<i16>(14), Ok(-69));
+		assert_eq!(bitbuf.read_varint32(), Ok(300));
+		assert_eq!(bitbuf.read_nul_string(), Ok("Hello".to_string()));
+		assert_eq!(
+			bitbuf.read_byte(),
+			Err(ReadError::OutOfBounds {
+				offset: 79,
+				requested: 8,
+				available: 1
+			})
+		);
+		assert_eq!(bitbuf.cursor, 79);
+	}
+);
+
+test!(
+	test_slice_reader_borrowed_lsb,
+	test_slice_reader_borrowed_msb,
+	bench_slice_reader_borrowed_lsb,
+	bench_slice_reader_borrowed_msb,
+	{
+		use std::borrow::Cow;
+
+		use crate::BitSliceReader;
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_string_nul("Hello");
+		bitbuf.write_bytes([1, 2]);
+		bitbuf.write_bit(true);
+		bitbuf.write_string("World");
+		bitbuf.write_string_nul("!");
+		let bytes = bitbuf.into_bytes();
+
+		let mut bitbuf = BitSliceReader::<Endian>::from_bytes(&bytes);
+		assert!(matches!(
+			bitbuf.read_nul_string_borrowed(),
+			Ok(Cow::Borrowed("Hello"))
+		));
+		assert!(matches!(
+			bitbuf.read_bytes_borrowed(2),
+			Ok(Cow::Borrowed(&[1, 2]))
+		));
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert!(
+			matches!(bitbuf.read_string_borrowed(5), Ok(Cow::Owned(string)) if string == "World")
+		);
+		assert!(
+			matches!(bitbuf.read_nul_string_borrowed(), Ok(Cow::Owned(string)) if string == "!")
+		);
+
+		let bytes = [0xFF, 0xFE];
+		let mut bitbuf = BitSliceReader::<Endian>::from_bytes(&bytes);
+		assert!(matches!(
+			bitbuf.read_string_borrowed(2),
+			Err(ReadError::InvalidUtf8 { offset: 0, .. })
+		));
+		assert_eq!(bitbuf.cursor, 0);
+	}
+);
+
+test!(
+	test_io_read_lsb,
+	test_io_read_msb,
+	bench_io_read_lsb,
+	bench_io_read_msb,
+	{
+		use std::io::Read;
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		bitbuf.write_bytes(b"Hello");
+		bitbuf.write_bit(false);
+		bitbuf.write_bit(true);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		let mut hello = [0; 5];
+		bitbuf.read_exact(&mut hello).unwrap();
+		assert_eq!(&hello, b"Hello");
+		assert_eq!(bitbuf.read_bit(), Ok(false));
+
+		// Only 6 bits are left, which isn't a whole byte
+		assert_eq!(Read::read(&mut bitbuf, &mut hello).unwrap(), 0);
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+	}
+);
+
+test!(
+	test_read_checkpoint_lsb,
+	test_read_checkpoint_msb,
+	bench_read_checkpoint_lsb,
+	bench_read_checkpoint_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_uint(5_u8, 3);
+		bitbuf.write_varint32(300);
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+
+		// Dropped without committing
+		{
+			let mut checkpoint = bitbuf.checkpoint();
+			assert_eq!(checkpoint.read_uint::<u8>(3), Ok(5));
+			assert!(checkpoint.read_uint::<u32>(32).is_err());
+		}
+		assert_eq!(bitbuf.cursor, 0);
+
+		let mut checkpoint = bitbuf.checkpoint();
+		assert_eq!(checkpoint.read_uint::<u8>(3), Ok(5));
+		checkpoint.rollback();
+		assert_eq!(bitbuf.cursor, 0);
+
+		let mut checkpoint = bitbuf.checkpoint();
+		assert_eq!(checkpoint.read_uint::<u8>(3), Ok(5));
+		assert_eq!(checkpoint.read_varint32(), Ok(300));
+		checkpoint.commit();
+		assert_eq!(bitbuf.cursor, 19);
+	}
+);
+
+test!(
+	test_read_peek_lsb,
+	test_read_peek_msb,
+	bench_read_peek_lsb,
+	bench_read_peek_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		bitbuf.write_int(-69_i16, 14);
+		bitbuf.write_uint(0x1A_u8, 5);
+		bitbuf.write_bytes([1, 2]);
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+
+		assert_eq!(bitbuf.peek_bit(), Ok(true));
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.peek_int::<i16>(14), Ok(-69));
+		assert_eq!(bitbuf.peek_uint::<u16>(14), bitbuf.read_uint::<u16>(14));
+		assert_eq!(bitbuf.cursor, 15);
+
+		// Look past the header without reading it
+		assert_eq!(bitbuf.peek_uint::<u8>(5), Ok(0x1A));
+		let message = bitbuf.peek_with(|reader| {
+			reader.advance(5);
+			reader.read_bytes(2)
+		});
+		assert_eq!(message, Ok(vec![1, 2]));
+		assert_eq!(bitbuf.cursor, 15);
+
+		bitbuf.advance(5);
+		assert_eq!(bitbuf.peek_bytes(2), Ok(vec![1, 2]));
+		assert_eq!(
+			bitbuf.peek_bytes(3),
+			Err(ReadError::OutOfBounds {
+				offset: 20,
+				requested: 24,
+				available: 20
+			})
+		);
+		assert_eq!(bitbuf.cursor, 20);
+	}
+);
+
+test!(
+	test_read_aligned_bytes_lsb,
+	test_read_aligned_bytes_msb,
+	bench_read_aligned_bytes_lsb,
+	bench_read_aligned_bytes_msb,
+	{
+		// Aligned and unaligned bulk reads must return the same bytes and errors
+		for offset in 0..=16 {
+			let mut bitbuf = BitVecWriter::<Endian>::default();
+			bitbuf.write_uint(0_u32, offset);
+			for byte in b"\x80\x01Hello\0world" {
+				bitbuf.write_byte(*byte);
+			}
+			bitbuf.write_uint(0x7F_u8, 7);
+
+			let mut bitbuf = BitVecReader::<Endian>::new(bitbuf.into_bitvec());
+			bitbuf.advance(offset);
+			assert_eq!(bitbuf.read_bytes(2), Ok(vec![0x80, 0x01]));
+			assert_eq!(bitbuf.read_nul_string(), Ok("Hello".to_string()));
+			assert_eq!(
+				bitbuf.read_nul_string(),
+				Err(ReadError::OutOfBounds {
+					offset: offset + 64,
+					requested: 48,
+					available: 47
+				})
+			);
+			assert_eq!(bitbuf.read_string(5), Ok("world".to_string()));
+			assert_eq!(bitbuf.bits_left(), 7);
+		}
+
+		// A slice starting partway into a byte isn't aligned to its memory
+		let bytes = [0xFF, b'H', b'i', 0, 0xFF];
+		let bits = &bitvec::slice::BitSlice::<Endian, u8>::from_slice(&bytes).unwrap()[4..];
+		let mut bitbuf = crate::BitSliceReader::new(bits);
+		assert_eq!(bitbuf.read_uint::<u8>(4), Ok(0xF));
+		assert_eq!(bitbuf.read_nul_string(), Ok("Hi".to_string()));
+		assert_eq!(bitbuf.read_bytes(1), Ok(vec![0xFF]));
+	}
+);
+
+test!(
+	test_read_uints_lsb,
+	test_read_uints_msb,
+	bench_read_uints_lsb,
+	bench_read_uints_msb,
+	{
+		// Every width, with and without a partial word at the end
+		for bits in 1..=32 {
+			for len in [0, 1, 7, 64 / bits, 64 / bits + 1, 100].iter() {
+				let uints: Vec<u32> = (0..*len as u32)
+					.map(|i| i.wrapping_mul(0x9E37_79B9) >> (32 - bits))
+					.collect();
+				let mut bitbuf = BitVecWriter::<Endian>::default();
+				bitbuf.write_uint(0b101_u8, 3);
+				for uint in &uints {
+					bitbuf.write_uint(*uint, bits);
+				}
+				bitbuf.write_bit(true);
+
+				let mut bitbuf = BitVecReader::<Endian>::new(bitbuf.into_bitvec());
+				bitbuf.advance(3);
+				let mut out = vec![0; *len];
+				assert_eq!(bitbuf.read_uints_into(&mut out, bits), Ok(()));
+				assert_eq!(out, uints);
+				assert_eq!(bitbuf.read_bit(), Ok(true));
+			}
+		}
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(vec![0xFF; 4]);
+		let mut out = [1; 5];
+		assert_eq!(
+			bitbuf.read_uints_into(&mut out, 7),
+			Err(ReadError::OutOfBounds {
+				offset: 0,
+				requested: 35,
+				available: 32
+			})
+		);
+		assert_eq!(
+			bitbuf.read_uints_into(&mut out, 33),
+			Err(ReadError::OutOfRange {
+				offset: 0,
+				bits: 33,
+				max: 32
+			})
+		);
+		assert_eq!(out, [1; 5]);
+		assert_eq!(bitbuf.cursor, 0);
+
+		// A slice starting partway into a byte
+		let bytes = [0x5A, 0xC3, 0x96];
+		let bits = &bitvec::slice::BitSlice::<Endian, u8>::from_slice(&bytes).unwrap()[4..];
+		let mut out = [0; 6];
+		assert_eq!(
+			crate::BitSliceReader::new(bits).read_uints_into(&mut out, 3),
+			Ok(())
+		);
+		let mut bitbuf = crate::BitSliceReader::new(bits);
+		for uint in &out {
+			assert_eq!(bitbuf.read_uint::<u32>(3), Ok(*uint));
+		}
 	}
 );
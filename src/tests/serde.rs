@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::serde::{from_reader, to_writer, Error};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Kind {
+	Empty,
+	Id(#[serde(with = "crate::serde::Bits::<4>")] u8),
+	Point { x: i16, y: i16 },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Packet {
+	flag: bool,
+	#[serde(with = "crate::serde::Bits::<12>")]
+	id: u16,
+	#[serde(with = "crate::serde::Bits::<5>")]
+	delta: i8,
+	#[serde(with = "crate::serde::varint")]
+	length: u64,
+	#[serde(with = "crate::serde::varint")]
+	offset: i32,
+	#[serde(with = "crate::serde::Bits::<3>")]
+	slot: Option<u8>,
+	name: String,
+	values: Vec<f32>,
+	kind: Kind,
+	tags: BTreeMap<char, u8>,
+}
+
+fn packet() -> Packet {
+	Packet {
+		flag: true,
+		id: 0xABC,
+		delta: -7,
+		length: 300,
+		offset: -2,
+		slot: Some(5),
+		name: "hello".to_string(),
+		values: vec![1.5, -0.25],
+		kind: Kind::Point { x: -1, y: 1024 },
+		tags: vec![('a', 1), ('ß', 2)].into_iter().collect(),
+	}
+}
+
+test!(
+	test_serde_roundtrip_lsb,
+	test_serde_roundtrip_msb,
+	bench_serde_roundtrip_lsb,
+	bench_serde_roundtrip_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		to_writer(&mut bitbuf, &packet()).unwrap();
+		to_writer(&mut bitbuf, &Kind::Empty).unwrap();
+		to_writer(&mut bitbuf, &Kind::Id(9)).unwrap();
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(from_reader::<_, Packet>(&mut bitbuf), Ok(packet()));
+		assert_eq!(from_reader::<_, Kind>(&mut bitbuf), Ok(Kind::Empty));
+		assert_eq!(from_reader::<_, Kind>(&mut bitbuf), Ok(Kind::Id(9)));
+		assert!(bitbuf.bits_left() < 8);
+	}
+);
+
+test!(
+	test_serde_layout_lsb,
+	test_serde_layout_msb,
+	bench_serde_layout_lsb,
+	bench_serde_layout_msb,
+	{
+		#[derive(Serialize)]
+		struct Header {
+			flag: bool,
+			#[serde(with = "crate::serde::Bits::<12>")]
+			id: u16,
+			#[serde(with = "crate::serde::varint")]
+			length: u32,
+			#[serde(with = "crate::serde::Bits::<3>")]
+			slot: Option<u8>,
+			none: Option<u32>,
+			#[serde(with = "crate::serde::Bits::<64>")]
+			wide: i64,
+		}
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		to_writer(
+			&mut bitbuf,
+			&Header {
+				flag: true,
+				id: 0xABC,
+				length: 300,
+				slot: Some(5),
+				none: None,
+				wide: -2,
+			},
+		)
+		.unwrap();
+		assert_eq!(bitbuf.bits_written(), 1 + 12 + 16 + 1 + 3 + 1 + 64);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.read_uint::<u16>(12), Ok(0xABC));
+		assert_eq!(bitbuf.read_varint32(), Ok(300));
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.read_uint::<u8>(3), Ok(5));
+		assert_eq!(bitbuf.read_bit(), Ok(false));
+		assert_eq!(bitbuf.read_int::<i64>(64), Ok(-2));
+	}
+);
+
+test!(
+	test_serde_errors_lsb,
+	test_serde_errors_msb,
+	bench_serde_errors_lsb,
+	bench_serde_errors_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::with_max_bits(16);
+		assert_eq!(to_writer(&mut bitbuf, &packet()), Err(Error::Overflow));
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		to_writer(&mut bitbuf, &"hello").unwrap();
+		let mut bytes = bitbuf.into_bytes();
+		bytes.pop();
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bytes);
+		assert_eq!(
+			from_reader::<_, String>(&mut bitbuf),
+			Err(Error::Read(ReadError::OutOfBounds {
+				offset: 8,
+				requested: 40,
+				available: 32
+			}))
+		);
+		assert_eq!(bitbuf.cursor, 0);
+
+		// A length prefix far past the end of the input fails before anything is read for it
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_varint64(1 << 61);
+		bitbuf.write_bytes(b"hello");
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(
+			from_reader::<_, String>(&mut bitbuf),
+			Err(Error::Read(ReadError::OutOfBounds {
+				offset: 72,
+				requested: usize::MAX,
+				available: 40
+			}))
+		);
+		assert_eq!(bitbuf.cursor, 0);
+		assert_eq!(
+			from_reader::<_, serde::de::IgnoredAny>(&mut bitbuf),
+			Err(Error::NotSelfDescribing)
+		);
+	}
+);
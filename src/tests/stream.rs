@@ -0,0 +1,217 @@
+use std::io::{self, Cursor, Read, Write};
+
+/// A source that returns one byte per read, to make the reader refill as often as possible.
+pub(super) struct Trickle<R>(pub(super) R);
+impl<R: Read> Read for Trickle<R> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		let len = buf.len().min(1);
+		self.0.read(&mut buf[..len])
+	}
+}
+
+/// A source that fails after its bytes run out.
+struct Broken<'a>(&'a [u8]);
+impl Read for Broken<'_> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		if self.0.is_empty() {
+			return Err(io::ErrorKind::ConnectionReset.into());
+		}
+		self.0.read(buf)
+	}
+}
+
+test!(
+	test_stream_reader_lsb,
+	test_stream_reader_msb,
+	bench_stream_reader_lsb,
+	bench_stream_reader_msb,
+	{
+		use crate::StreamBitReader;
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		bitbuf.write_int(-69_i16, 14);
+		bitbuf.write_varint64(u64::MAX);
+		bitbuf.write_string_nul("Hello, world!");
+		bitbuf.write_bytes([1, 2, 3]);
+		bitbuf.write_uints(&[1, 2, 3, 4, 5, 6, 7], 10);
+		bitbuf.write_bit_vec3_coord([1.5, -2.25, 0.]);
+		let bytes = bitbuf.into_bytes();
+
+		let mut bitbuf = StreamBitReader::<_, Endian>::with_capacity(Trickle(&bytes[..]), 1);
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.read_int::<i16>(14), Ok(-69));
+		assert_eq!(bitbuf.read_varint64(), Ok(u64::MAX));
+		assert_eq!(bitbuf.read_nul_string(), Ok("Hello, world!".to_string()));
+		assert_eq!(bitbuf.read_bytes(3), Ok(vec![1, 2, 3]));
+		let mut uints = [0; 7];
+		assert_eq!(bitbuf.read_uints_into(&mut uints, 10), Ok(()));
+		assert_eq!(uints, [1, 2, 3, 4, 5, 6, 7]);
+		assert_eq!(bitbuf.read_bit_vec3_coord(), Ok([1.5, -2.25, 0.]));
+		let bits_read = bitbuf.bits_read();
+		assert_eq!(
+			bitbuf.read_uint::<u8>(8),
+			Err(ReadError::OutOfBounds {
+				offset: bits_read,
+				requested: 8,
+				available: bytes.len() * 8 - bits_read
+			})
+		);
+		assert!(bitbuf.is_eof());
+		assert_eq!(bitbuf.bits_read(), bits_read);
+	}
+);
+
+test!(
+	test_stream_reader_skip_lsb,
+	test_stream_reader_skip_msb,
+	bench_stream_reader_skip_lsb,
+	bench_stream_reader_skip_msb,
+	{
+		use crate::StreamBitReader;
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_uint(5_u8, 3);
+		bitbuf.write_bytes([0; 100]);
+		bitbuf.write_uint(0x1234_u16, 13);
+		bitbuf.write_byte(0xAB);
+		let bytes = bitbuf.into_bytes();
+
+		let mut bitbuf = StreamBitReader::<_, Endian>::with_capacity(&bytes[..], 4);
+		assert_eq!(bitbuf.read_uint::<u8>(3), Ok(5));
+		bitbuf.skip(800).unwrap();
+		assert_eq!(bitbuf.bits_read(), 803);
+		assert_eq!(bitbuf.read_uint::<u16>(13), Ok(0x1234));
+
+		// Seek back to the start, and forward past the buffer
+		let mut bitbuf = StreamBitReader::<_, Endian>::with_capacity(Cursor::new(&bytes), 4);
+		bitbuf.seek_bits(816).unwrap();
+		assert_eq!(bitbuf.read_byte(), Ok(0xAB));
+		bitbuf.seek_bits(0).unwrap();
+		assert_eq!(bitbuf.read_uint::<u8>(3), Ok(5));
+		bitbuf.seek_bits(803).unwrap();
+		assert_eq!(bitbuf.read_uint::<u16>(13), Ok(0x1234));
+
+		let mut bitbuf = StreamBitReader::<_, Endian>::new(&bytes[..]);
+		assert_eq!(
+			bitbuf.skip(1000),
+			Err(ReadError::OutOfBounds {
+				offset: 0,
+				requested: 1000,
+				available: 824
+			})
+		);
+	}
+);
+
+test!(
+	test_stream_reader_io_error_lsb,
+	test_stream_reader_io_error_msb,
+	bench_stream_reader_io_error_lsb,
+	bench_stream_reader_io_error_msb,
+	{
+		use crate::StreamBitReader;
+
+		let mut bitbuf = StreamBitReader::<_, Endian>::new(Broken(&[0xFF, 0xFF]));
+		assert_eq!(bitbuf.read_uint::<u8>(4), Ok(0xF));
+		assert_eq!(
+			bitbuf.read_uint::<u16>(16),
+			Err(ReadError::Io {
+				offset: 4,
+				kind: io::ErrorKind::ConnectionReset
+			})
+		);
+		assert_eq!(bitbuf.bits_read(), 4);
+		assert_eq!(bitbuf.read_uint::<u16>(12), Ok(0xFFF));
+	}
+);
+
+/// A sink that takes `.0` bytes and then fails.
+struct Full(usize);
+impl Write for Full {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		if self.0 == 0 {
+			return Err(io::ErrorKind::WriteZero.into());
+		}
+		let len = buf.len().min(self.0);
+		self.0 -= len;
+		Ok(len)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+test!(
+	test_stream_writer_lsb,
+	test_stream_writer_msb,
+	bench_stream_writer_lsb,
+	bench_stream_writer_msb,
+	{
+		use crate::StreamBitWriter;
+
+		let mut expected = BitVecWriter::<Endian>::default();
+		let mut bitbuf = StreamBitWriter::<_, Endian>::new(Vec::new());
+		macro_rules! both {
+			( $($write:tt)* ) => {
+				expected.$($write)*;
+				bitbuf.$($write)*;
+			};
+		}
+		both!(write_bit(true));
+		assert_eq!(bitbuf.bits_pending(), 1);
+		assert!(bitbuf.get_ref().is_empty());
+		both!(write_int(-69_i16, 14));
+		assert_eq!(bitbuf.bits_pending(), 7);
+		assert_eq!(bitbuf.get_ref().len(), 1);
+		both!(write_varint64(u64::MAX));
+		both!(write_string_nul("Hello, world!"));
+		both!(write_bytes([1, 2, 3]));
+		both!(write_uints(&[1, 2, 3, 4, 5, 6, 7], 10));
+		both!(write_bit_vec3_coord([1.5, -2.25, 0.]));
+		bitbuf.write_all(&[4, 5]).unwrap();
+		expected.write_bytes([4, 5]);
+		both!(write_uint(3_u8, 2));
+		assert_eq!(bitbuf.bits_written(), expected.bits_written());
+		assert_eq!(bitbuf.bits_pending(), expected.bits_written() % 8);
+
+		assert_eq!(bitbuf.finish().unwrap(), expected.into_bytes());
+	}
+);
+
+test!(
+	test_stream_writer_io_error_lsb,
+	test_stream_writer_io_error_msb,
+	bench_stream_writer_io_error_lsb,
+	bench_stream_writer_io_error_msb,
+	{
+		use crate::StreamBitWriter;
+
+		let mut bitbuf = StreamBitWriter::<_, Endian>::new(Full(2));
+		bitbuf.write_uint(0xFFF_u16, 12);
+		assert!(bitbuf.error().is_none());
+		bitbuf.write_bytes([1, 2]);
+		assert_eq!(
+			bitbuf.error().map(io::Error::kind),
+			Some(io::ErrorKind::WriteZero)
+		);
+		assert_eq!(
+			bitbuf.write(&[3]).map_err(|error| error.kind()),
+			Err(io::ErrorKind::WriteZero)
+		);
+		assert_eq!(
+			bitbuf.finish().map_err(|error| error.kind()).err(),
+			Some(io::ErrorKind::WriteZero)
+		);
+
+		// The padding is only written by `finish`
+		let mut bitbuf = StreamBitWriter::<_, Endian>::new(Full(1));
+		bitbuf.write_uint(1_u8, 3);
+		assert!(bitbuf.finish().is_ok());
+		let mut bitbuf = StreamBitWriter::<_, Endian>::new(Full(0));
+		bitbuf.write_uint(1_u8, 3);
+		assert!(bitbuf.error().is_none());
+		assert!(bitbuf.finish().is_err());
+	}
+);
@@ -97,3 +97,534 @@ test!(
 		assert_eq!(bitbuf.into_bytes(), b"Hello, world!");
 	}
 );
+
+test!(
+	test_write_overflow_lsb,
+	test_write_overflow_msb,
+	bench_write_overflow_lsb,
+	bench_write_overflow_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::with_max_bits(20);
+		bitbuf.write_bit(true);
+		bitbuf.write_uint(69_u16, 12);
+		assert_eq!(bitbuf.bits_left(), Some(7));
+		assert!(!bitbuf.is_overflowed());
+
+		bitbuf.write_byte(0xFF);
+		assert!(bitbuf.is_overflowed());
+		assert_eq!(bitbuf.bits_written(), 13);
+
+		// The overflow flag is sticky, even for writes that would fit
+		bitbuf.write_bit(true);
+		bitbuf.write_int(-1_i8, 2);
+		bitbuf.write_string("");
+		assert!(bitbuf.is_overflowed());
+		assert_eq!(bitbuf.bits_written(), 13);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.read_uint(12), Ok(69_u16));
+		assert_eq!(bitbuf.bits_left(), 3);
+	}
+);
+
+test!(
+	test_write_overflow_exact_lsb,
+	test_write_overflow_exact_msb,
+	bench_write_overflow_exact_lsb,
+	bench_write_overflow_exact_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::with_max_bits(96);
+		bitbuf.write_float(69.69_f32);
+		bitbuf.write_double(69.69_f64);
+		assert!(!bitbuf.is_overflowed());
+		assert_eq!(bitbuf.bits_left(), Some(0));
+
+		bitbuf.write_string_nul("");
+		assert!(bitbuf.is_overflowed());
+		assert_eq!(bitbuf.bytes_written(), 12);
+	}
+);
+
+#[test]
+fn test_write_varint() {
+	use crate::BitVecWriter;
+	use bitvec::prelude::Lsb0;
+
+	let mut bitbuf = BitVecWriter::<Lsb0>::default();
+	bitbuf.write_varint32(1);
+	bitbuf.write_varint32(300);
+	bitbuf.write_signed_varint32(-2);
+	bitbuf.write_varint64(u64::MAX);
+	assert_eq!(
+		bitbuf.into_bytes(),
+		&[0x01, 0xAC, 0x02, 0x03, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01]
+	);
+}
+
+#[test]
+fn test_write_field_index() {
+	use crate::BitVecWriter;
+	use bitvec::prelude::Lsb0;
+
+	let mut bitbuf = BitVecWriter::<Lsb0>::default();
+	bitbuf.write_field_index(-1, Some(0), true);
+	bitbuf.write_field_index(0, Some(3), true);
+	bitbuf.write_field_index(3, Some(100), true);
+	bitbuf.write_field_index(100, None, false);
+	assert_eq!(bitbuf.bits_written(), 1 + 5 + 11 + 14);
+	assert_eq!(
+		bitbuf.into_bytes(),
+		&[0b0001_0101, 0b1010_0000, 0b1111_1111, 0b0111_1111]
+	);
+}
+
+#[test]
+#[should_panic(expected = "can't be delta-encoded")]
+fn test_write_field_index_overflow() {
+	use crate::BitVecWriter;
+	use bitvec::prelude::Lsb0;
+
+	// The delta doesn't fit in an i32, which must not wrap around to one that does
+	let mut bitbuf = BitVecWriter::<Lsb0>::default();
+	bitbuf.write_field_index(-1, Some(i32::MAX), true);
+}
+
+#[test]
+#[should_panic(expected = "bit angles can't be 0 bits wide")]
+fn test_write_bit_angle_zero_bits() {
+	use crate::BitVecWriter;
+	use bitvec::prelude::Lsb0;
+
+	// `read_bit_angle` refuses 0 bits, so writing nothing would make an angle that can't be read back
+	let mut bitbuf = BitVecWriter::<Lsb0>::default();
+	bitbuf.write_bit_angle(90.0, 0);
+}
+
+#[test]
+fn test_write_bit_coord() {
+	use crate::BitVecWriter;
+	use bitvec::prelude::Lsb0;
+
+	let mut bitbuf = BitVecWriter::<Lsb0>::default();
+	bitbuf.write_bit_coord(1.5);
+	assert_eq!(bitbuf.bits_written(), 22);
+	assert_eq!(bitbuf.into_bytes(), &[0b0000_0011, 0, 0b0010_0000]);
+
+	let mut bitbuf = BitVecWriter::<Lsb0>::default();
+	bitbuf.write_bit_coord_mp(-1.5, false, true);
+	assert_eq!(bitbuf.bits_written(), 17);
+	assert_eq!(bitbuf.into_bytes(), &[0b0000_0111, 0, 0b0000_0001]);
+}
+
+test!(
+	test_slice_writer_lsb,
+	test_slice_writer_msb,
+	bench_slice_writer_lsb,
+	bench_slice_writer_msb,
+	{
+		use crate::BitSliceWriter;
+
+		let mut expected = BitVecWriter::<Endian>::default();
+		expected.write_bit(true);
+		expected.write_int(-69_i16, 14);
+		expected.write_varint32(300);
+		expected.write_string_nul("Hello");
+		let expected = expected.into_bytes();
+
+		// Garbage in the buffer must not leak into the written bits
+		let mut buf = [0xAA; 10];
+		let mut bitbuf = BitSliceWriter::<Endian>::from_bytes(&mut buf);
+		bitbuf.write_bit(true);
+		bitbuf.write_int(-69_i16, 14);
+		bitbuf.write_varint32(300);
+		bitbuf.write_string_nul("Hello");
+		assert!(!bitbuf.is_overflowed());
+		assert_eq!(bitbuf.bits_written(), 79);
+		assert_eq!(bitbuf.bytes_written(), 10);
+		assert_eq!(bitbuf.bits_left(), Some(1));
+		assert_eq!(&bitbuf.written_bytes()[..9], &expected[..9]);
+	}
+);
+
+test!(
+	test_slice_writer_overflow_lsb,
+	test_slice_writer_overflow_msb,
+	bench_slice_writer_overflow_lsb,
+	bench_slice_writer_overflow_msb,
+	{
+		use crate::BitSliceWriter;
+
+		let mut buf = [0; 3];
+		let mut bitbuf = BitSliceWriter::<Endian>::from_bytes(&mut buf);
+		bitbuf.write_bit(true);
+		bitbuf.write_uint(69_u16, 12);
+		assert_eq!(bitbuf.bits_left(), Some(11));
+
+		bitbuf.write_uint(0xFFF_u16, 12);
+		assert!(bitbuf.is_overflowed());
+		bitbuf.write_bit(true);
+		assert_eq!(bitbuf.bits_written(), 13);
+		assert_eq!(bitbuf.written_bytes().len(), 2);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(buf.to_vec());
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.read_uint(12), Ok(69_u16));
+		assert_eq!(bitbuf.read_uint(11), Ok(0_u16));
+	}
+);
+
+test!(
+	test_array_writer_lsb,
+	test_array_writer_msb,
+	bench_array_writer_lsb,
+	bench_array_writer_msb,
+	{
+		use crate::ArrayBitWriter;
+
+		let mut expected = BitVecWriter::<Endian>::default();
+		expected.write_bit(true);
+		expected.write_int(-69_i16, 14);
+		expected.write_string_nul("Hello");
+		let expected = expected.into_bytes();
+
+		let mut bitbuf = ArrayBitWriter::<Endian, 8>::new();
+		bitbuf.write_bit(true);
+		bitbuf.write_int(-69_i16, 14);
+		bitbuf.write_string_nul("Hello");
+		assert!(!bitbuf.is_overflowed());
+		assert_eq!(bitbuf.bits_written(), 63);
+		assert_eq!(bitbuf.bits_left(), Some(1));
+		assert_eq!(bitbuf.as_bytes(), &expected[..]);
+
+		let mut reader = bitbuf.as_reader();
+		assert_eq!(reader.read_bit(), Ok(true));
+		assert_eq!(reader.read_int::<i16>(14), Ok(-69));
+		assert_eq!(reader.read_nul_string(), Ok("Hello".to_string()));
+		assert_eq!(reader.bits_left(), 0);
+
+		bitbuf.write_byte(0xFF);
+		assert!(bitbuf.is_overflowed());
+		bitbuf.write_bit(true);
+		assert_eq!(bitbuf.bits_written(), 63);
+	}
+);
+
+test!(
+	test_io_write_lsb,
+	test_io_write_msb,
+	bench_io_write_lsb,
+	bench_io_write_msb,
+	{
+		use std::io::Write;
+
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		bitbuf.write_all(b"Hello").unwrap();
+		bitbuf.write_bit(true);
+
+		let mut bitbuf = BitVecReader::<Endian>::from_bytes(bitbuf.into_bytes());
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.read_string(5), Ok("Hello".to_string()));
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+
+		// A bounded writer takes as many whole bytes as fit
+		let mut bitbuf = BitVecWriter::<Endian>::with_max_bits(20);
+		bitbuf.write_bit(true);
+		assert_eq!(Write::write(&mut bitbuf, &[1, 2, 3]).unwrap(), 2);
+		assert!(!bitbuf.is_overflowed());
+		assert_eq!(Write::write(&mut bitbuf, &[3]).unwrap(), 0);
+		assert!(bitbuf.is_overflowed());
+		assert_eq!(bitbuf.bits_written(), 17);
+	}
+);
+
+test!(
+	test_write_checkpoint_lsb,
+	test_write_checkpoint_msb,
+	bench_write_checkpoint_lsb,
+	bench_write_checkpoint_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::with_max_bits(32);
+		bitbuf.write_uint(5_u8, 3);
+
+		// An update that doesn't fit is dropped, along with the overflow
+		{
+			let mut checkpoint = bitbuf.checkpoint();
+			checkpoint.write_uint(0xFFFF_u16, 16);
+			checkpoint.write_uint(0xFFFF_u16, 16);
+			assert!(checkpoint.is_overflowed());
+		}
+		assert_eq!(bitbuf.bits_written(), 3);
+		assert_eq!(bitbuf.bitvec.len(), 3);
+		assert!(!bitbuf.is_overflowed());
+
+		let mut checkpoint = bitbuf.checkpoint();
+		checkpoint.write_byte(0xAB);
+		checkpoint.rollback();
+		assert_eq!(bitbuf.bits_written(), 3);
+
+		let mut checkpoint = bitbuf.checkpoint();
+		checkpoint.write_uint(0x1234_u16, 16);
+		checkpoint.commit();
+		assert_eq!(bitbuf.bits_written(), 19);
+
+		let mut bitbuf = BitVecReader::<Endian>::new(bitbuf.into_bitvec());
+		assert_eq!(bitbuf.read_uint::<u8>(3), Ok(5));
+		assert_eq!(bitbuf.read_uint::<u16>(16), Ok(0x1234));
+		assert_eq!(bitbuf.bits_left(), 0);
+	}
+);
+
+test!(
+	test_write_seek_lsb,
+	test_write_seek_msb,
+	bench_write_seek_lsb,
+	bench_write_seek_msb,
+	{
+		// Patch a length written before the data it counts
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_uint(5_u8, 3);
+		bitbuf.write_uint(0_u16, 12);
+		bitbuf.write_string("Hello");
+		bitbuf.seek(3);
+		bitbuf.write_uint(40_u16, 12);
+		assert_eq!(bitbuf.cursor(), 15);
+		assert_eq!(bitbuf.bits_written(), 55);
+		assert_eq!(crate::BitWrite::bits_written(&bitbuf), 55);
+
+		// An overwrite that runs past the end extends the bitvec
+		bitbuf.seek(47);
+		bitbuf.write_uint(0x1AB_u16, 9);
+		assert_eq!(bitbuf.bits_written(), 56);
+
+		// Skipping past the end leaves zeros
+		bitbuf.advance(5);
+		bitbuf.write_bit(true);
+		assert_eq!(bitbuf.bits_written(), 62);
+
+		// A checkpoint restores the bits it overwrites
+		bitbuf.seek(0);
+		{
+			let mut checkpoint = bitbuf.checkpoint();
+			checkpoint.write_uint(2_u8, 3);
+		}
+		assert_eq!(bitbuf.cursor(), 0);
+
+		let mut bitbuf = BitVecReader::<Endian>::new(bitbuf.into_bitvec());
+		assert_eq!(bitbuf.read_uint::<u8>(3), Ok(5));
+		assert_eq!(bitbuf.read_uint::<u16>(12), Ok(40));
+		assert_eq!(bitbuf.read_string(4), Ok("Hell".to_string()));
+		assert_eq!(bitbuf.read_uint::<u16>(9), Ok(0x1AB));
+		assert_eq!(bitbuf.read_uint::<u8>(5), Ok(0));
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.bits_left(), 0);
+
+		// A writer over existing bytes starts after them
+		let mut bitbuf = BitVecWriter::<Endian>::from_bytes(vec![0xAB]);
+		assert_eq!(bitbuf.cursor(), 8);
+		assert_eq!(bitbuf.bits_written(), 8);
+		bitbuf.write_byte(0xCD);
+		assert_eq!(bitbuf.into_bytes(), vec![0xAB, 0xCD]);
+	}
+);
+
+test!(
+	test_write_insert_lsb,
+	test_write_insert_msb,
+	bench_write_insert_lsb,
+	bench_write_insert_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_string("Hlo");
+		bitbuf.set_insert_mode(true);
+		bitbuf.seek(8);
+		bitbuf.write_string("el");
+		assert_eq!(bitbuf.cursor(), 24);
+		assert_eq!(bitbuf.bits_written(), 40);
+
+		// Unaligned inserts shift everything after them, and inserting at the end appends
+		bitbuf.seek(0);
+		bitbuf.write_uint(5_u8, 3);
+		bitbuf.seek(43);
+		bitbuf.write_bit(true);
+
+		let mut bitbuf = BitVecReader::<Endian>::new(bitbuf.into_bitvec());
+		assert_eq!(bitbuf.read_uint::<u8>(3), Ok(5));
+		assert_eq!(bitbuf.read_string(5), Ok("Hello".to_string()));
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.bits_left(), 0);
+
+		// An insert counts against the limit even before the end
+		let mut bitbuf = BitVecWriter::<Endian>::with_max_bits(16);
+		bitbuf.write_uint(0xFFFF_u16, 16);
+		bitbuf.seek(0);
+		bitbuf.write_byte(0);
+		assert!(!bitbuf.is_overflowed());
+		bitbuf.set_insert_mode(true);
+		bitbuf.seek(0);
+		bitbuf.write_bit(true);
+		assert!(bitbuf.is_overflowed());
+		assert_eq!(bitbuf.into_bytes(), vec![0, 0xFF]);
+	}
+);
+
+test!(
+	test_write_placeholder_lsb,
+	test_write_placeholder_msb,
+	bench_write_placeholder_lsb,
+	bench_write_placeholder_msb,
+	{
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		let count = bitbuf.reserve_uint::<u16>(10);
+		assert_eq!(count.offset(), Some(1));
+		assert_eq!(count.bits(), 10);
+		bitbuf.write_string("Hello");
+		bitbuf.fill(count, 5);
+		assert_eq!(bitbuf.cursor(), 51);
+
+		bitbuf.write_length_prefixed(7, |bitbuf| {
+			bitbuf.write_varint32(300);
+			bitbuf.write_length_prefixed(4, |bitbuf| bitbuf.write_uint(1_u8, 3));
+		});
+		bitbuf.write_byte(0xAB);
+
+		let mut bitbuf = BitVecReader::<Endian>::new(bitbuf.into_bitvec());
+		assert_eq!(bitbuf.read_bit(), Ok(true));
+		assert_eq!(bitbuf.read_uint::<u16>(10), Ok(5));
+		assert_eq!(bitbuf.read_string(5), Ok("Hello".to_string()));
+		assert_eq!(bitbuf.read_uint::<u8>(7), Ok(16 + 4 + 3));
+		assert_eq!(bitbuf.read_varint32(), Ok(300));
+		assert_eq!(bitbuf.read_uint::<u8>(4), Ok(3));
+		assert_eq!(bitbuf.read_uint::<u8>(3), Ok(1));
+		assert_eq!(bitbuf.read_byte(), Ok(0xAB));
+	}
+);
+
+test!(
+	test_write_placeholder_overflow_lsb,
+	test_write_placeholder_overflow_msb,
+	bench_write_placeholder_overflow_lsb,
+	bench_write_placeholder_overflow_msb,
+	{
+		// A sub-message too long for its length is dropped
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bit(true);
+		bitbuf.write_length_prefixed(3, |bitbuf| bitbuf.write_byte(0xFF));
+		assert!(bitbuf.is_overflowed());
+		assert_eq!(bitbuf.bits_written(), 1);
+
+		// So is one that doesn't fit in the writer
+		let mut bitbuf = BitVecWriter::<Endian>::with_max_bits(16);
+		bitbuf.write_length_prefixed(8, |bitbuf| bitbuf.write_uint(0_u16, 9));
+		assert!(bitbuf.is_overflowed());
+		assert_eq!(bitbuf.bits_written(), 0);
+
+		// And so is one that seeks back before where it started
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_byte(0xAB);
+		bitbuf.write_length_prefixed(8, |bitbuf| bitbuf.seek(0));
+		assert!(bitbuf.is_overflowed());
+		assert_eq!(bitbuf.cursor(), 8);
+		assert_eq!(bitbuf.into_bytes(), [0xAB]);
+
+		// And so is a length too wide for any prefix
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_length_prefixed(70, |bitbuf| bitbuf.write_bit(true));
+		assert!(bitbuf.is_overflowed());
+		assert_eq!(bitbuf.bits_written(), 0);
+
+		// A placeholder wider than its type is refused
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		let placeholder = bitbuf.reserve_uint::<u8>(9);
+		assert_eq!(placeholder.offset(), None);
+		assert!(bitbuf.is_overflowed());
+		bitbuf.fill(placeholder, 0xFF);
+		assert_eq!(bitbuf.bits_written(), 0);
+
+		// And a value too wide for its placeholder leaves it zeroed
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		let placeholder = bitbuf.reserve_uint::<u16>(8);
+		bitbuf.fill(placeholder, 300);
+		assert!(bitbuf.is_overflowed());
+		assert_eq!(bitbuf.into_bytes(), vec![0]);
+
+		let mut bitbuf = BitVecWriter::<Endian>::with_max_bits(4);
+		let placeholder = bitbuf.reserve_uint::<u8>(8);
+		assert_eq!(placeholder.offset(), None);
+		bitbuf.fill(placeholder, 0xFF);
+		assert_eq!(bitbuf.bits_written(), 0);
+	}
+);
+
+test!(
+	test_write_aligned_bytes_lsb,
+	test_write_aligned_bytes_msb,
+	bench_write_aligned_bytes_lsb,
+	bench_write_aligned_bytes_msb,
+	{
+		// Aligned and unaligned bulk writes must produce the same bits
+		for offset in 0..=16 {
+			let mut bitbuf = BitVecWriter::<Endian>::default();
+			bitbuf.write_uint(0_u32, offset);
+			bitbuf.write_bytes([0x00, 0x80, 0x01, 0xFF]);
+			bitbuf.write_string("Hello");
+			bitbuf.write_string_nul("world");
+			bitbuf.write_bit(true);
+
+			let mut bitbuf = BitVecReader::<Endian>::new(bitbuf.into_bitvec());
+			bitbuf.advance(offset);
+			for byte in [0x00, 0x80, 0x01, 0xFF].iter().chain(b"Helloworld\0") {
+				assert_eq!(bitbuf.read_byte(), Ok(*byte));
+			}
+			assert_eq!(bitbuf.read_bit(), Ok(true));
+			assert_eq!(bitbuf.bits_left(), 0);
+		}
+
+		// Overwriting in the middle clears the bits under the copy
+		let mut bitbuf = BitVecWriter::<Endian>::default();
+		bitbuf.write_bytes([0xFF; 4]);
+		bitbuf.seek(8);
+		bitbuf.write_string_nul("A");
+		assert_eq!(bitbuf.into_bytes(), [0xFF, b'A', 0, 0xFF]);
+	}
+);
+
+test!(
+	test_write_uints_lsb,
+	test_write_uints_msb,
+	bench_write_uints_lsb,
+	bench_write_uints_msb,
+	{
+		// Every width, with and without a partial word at the end
+		for bits in 1..=32 {
+			for len in [0, 1, 7, 64 / bits, 64 / bits + 1, 100].iter() {
+				let uints: Vec<u32> = (0..*len as u32)
+					.map(|i| i.wrapping_mul(0x9E37_79B9))
+					.collect();
+				let mut bitbuf = BitVecWriter::<Endian>::default();
+				bitbuf.write_uint(0b101_u8, 3);
+				bitbuf.write_uints(&uints, bits);
+				bitbuf.write_bit(true);
+				assert_eq!(bitbuf.bits_written(), 3 + len * bits + 1);
+
+				// Only the low bits of each uint are written
+				let mut bitbuf = BitVecReader::<Endian>::new(bitbuf.into_bitvec());
+				bitbuf.advance(3);
+				for uint in &uints {
+					assert_eq!(
+						bitbuf.read_uint::<u32>(bits),
+						Ok(uint & (u32::MAX >> (32 - bits)))
+					);
+				}
+				assert_eq!(bitbuf.read_bit(), Ok(true));
+			}
+		}
+
+		let mut bitbuf = crate::ArrayBitWriter::<Endian, 2>::new();
+		bitbuf.write_uints(&[1, 2, 3], 6);
+		assert!(bitbuf.is_overflowed());
+		assert_eq!(bitbuf.bits_written(), 0);
+	}
+);
@@ -1,18 +1,78 @@
-use bitvec::{macros::internal::funty::{IsFloat, IsNumber, IsSigned, IsUnsigned}, order::{BitOrder, Lsb0, Msb0}, slice::BitSlice, store::BitStore, vec::BitVec, view::{AsBits, BitView}};
+use std::{
+	io,
+	marker::PhantomData,
+	ops::{Deref, DerefMut},
+};
 
-use crate::BitCount;
+use bitvec::{
+	domain::DomainMut,
+	field::BitField,
+	macros::internal::funty::{IsFloat, IsNumber, IsSigned, IsUnsigned},
+	mem::BitMemory,
+	order::{BitOrder, Lsb0, Msb0},
+	slice::BitSlice,
+	store::BitStore,
+	vec::BitVec,
+};
 
+use crate::{
+	cached::specialize_width,
+	coord::{
+		COORD_DENOMINATOR, COORD_DENOMINATOR_LOWPRECISION, COORD_FRACTIONAL_BITS,
+		COORD_FRACTIONAL_BITS_MP_LOWPRECISION, COORD_INTEGER_BITS, COORD_INTEGER_BITS_MP,
+		COORD_RESOLUTION, COORD_RESOLUTION_LOWPRECISION, NORMAL_DENOMINATOR,
+		NORMAL_FRACTIONAL_BITS, NORMAL_RESOLUTION,
+	},
+	BitCount, BitSliceReader, CacheOrder, ToBits, FIELD_INDEX_END,
+};
+
+/// Writes bits into a [`BitVec`] at a cursor, which starts at the end of the bitvec.
+///
+/// Writes overwrite the bits at the cursor and extend the bitvec where they run past its end, so a value written
+/// earlier can be patched by [`seek`](BitVecWriter::seek)ing back to it. In
+/// [insert mode](BitVecWriter::set_insert_mode), writes shift the bits after the cursor along instead of
+/// overwriting them. Seeking or [`advance`](BitVecWriter::advance)ing past the end leaves a gap that the next write
+/// fills with zeros.
+///
+/// A writer created with [`with_max_bits`](BitVecWriter::with_max_bits) refuses any write that would
+/// grow the bitvec past that many bits. A refused write writes nothing and sets a sticky overflow flag,
+/// after which every further write is refused too, so a packet can be filled greedily and checked once
+/// with [`is_overflowed`](BitVecWriter::is_overflowed).
 #[derive(Debug, Clone, Default)]
 pub struct BitVecWriter<O: BitOrder> {
 	cursor: usize,
+	max_bits: Option<usize>,
+	overflowed: bool,
+	insert: bool,
 	pub bitvec: BitVec<O, u8>,
 }
 impl<O: BitOrder> BitVecWriter<O> {
+	/// Creates a writer with its cursor at the end of `bitvec`. [`seek`](BitVecWriter::seek) to 0 to overwrite the
+	/// bits instead.
 	#[inline]
 	pub fn new(bitvec: BitVec<O, u8>) -> BitVecWriter<O> {
-		BitVecWriter { cursor: 0, bitvec }
+		BitVecWriter {
+			cursor: bitvec.len(),
+			max_bits: None,
+			overflowed: false,
+			insert: false,
+			bitvec,
+		}
+	}
+
+	/// Creates an empty writer that can hold at most `max_bits` bits.
+	#[inline]
+	pub fn with_max_bits(max_bits: usize) -> BitVecWriter<O> {
+		BitVecWriter {
+			cursor: 0,
+			max_bits: Some(max_bits),
+			overflowed: false,
+			insert: false,
+			bitvec: BitVec::with_capacity(max_bits),
+		}
 	}
 
+	/// Creates a writer that writes after the end of `bytes`, like [`new`](BitVecWriter::new).
 	#[inline]
 	pub fn from_bytes(bytes: Vec<u8>) -> BitVecWriter<O> {
 		bytes.into()
@@ -33,15 +93,35 @@ impl<O: BitOrder> BitVecWriter<O> {
 		self.bitvec
 	}
 
+	/// Returns the length of the bitvec, which is where the bits written end, wherever the cursor is. See
+	/// [`cursor`](BitVecWriter::cursor) for where the next write goes.
 	#[inline]
 	pub fn bits_written(&self) -> usize {
-		self.cursor
+		self.bitvec.len()
 	}
 
 	/// Returns the number of bytes written to the bitvec, rounded up to the byte.
 	#[inline]
 	pub fn bytes_written(&self) -> usize {
-		((self.bits_written() as f32) / 8.).ceil() as usize
+		(self.bits_written() + 7) / 8
+	}
+
+	#[inline]
+	pub fn max_bits(&self) -> Option<usize> {
+		self.max_bits
+	}
+
+	/// Returns the number of bits that can still be written before the writer overflows, or `None` if it has no limit.
+	#[inline]
+	pub fn bits_left(&self) -> Option<usize> {
+		self.max_bits
+			.map(|max_bits| max_bits.saturating_sub(self.bitvec.len()))
+	}
+
+	/// Returns whether a write has been refused because it would have exceeded [`max_bits`](BitVecWriter::max_bits).
+	#[inline]
+	pub fn is_overflowed(&self) -> bool {
+		self.overflowed
 	}
 
 	#[inline]
@@ -54,6 +134,24 @@ impl<O: BitOrder> BitVecWriter<O> {
 		self.cursor
 	}
 
+	/// Moves the cursor to `bit`, which can be past the end of the bitvec.
+	#[inline]
+	pub fn seek(&mut self, bit: usize) {
+		self.cursor = bit;
+	}
+
+	/// Sets whether writes before the end of the bitvec shift the bits after the cursor along rather than
+	/// overwriting them.
+	#[inline]
+	pub fn set_insert_mode(&mut self, insert: bool) {
+		self.insert = insert;
+	}
+
+	#[inline]
+	pub fn is_insert_mode(&self) -> bool {
+		self.insert
+	}
+
 	/// Sets the bit at the given index. Will panic if the index is out of bounds.
 	#[inline]
 	pub fn set_bit(&mut self, index: usize, bit: bool) {
@@ -68,139 +166,1154 @@ impl<O: BitOrder> BitVecWriter<O> {
 		}
 	}
 
+	/// Saves the position and the bits after it, returning a guard that restores them when dropped unless it is
+	/// [`commit`](WriterCheckpoint::commit)ted. The writer can be used through the guard in the meantime.
 	#[inline]
-	pub fn write_bit(&mut self, bit: bool) {
-		self.bitvec.push(bit);
-		self.advance(1);
+	pub fn checkpoint(&mut self) -> WriterCheckpoint<'_, O> {
+		let tail_start = self.cursor.min(self.bitvec.len());
+		WriterCheckpoint {
+			cursor: self.cursor,
+			tail_start,
+			tail: self.bitvec[tail_start..].to_bitvec(),
+			overflowed: self.overflowed,
+			committed: false,
+			writer: self,
+		}
 	}
 
+	/// Returns the number of bits [`reserve`](Self::reserve) can still hand out.
 	#[inline]
-	pub fn write_byte(&mut self, byte: u8) {
-		let bits = byte.view_bits();
-		self.bitvec.extend_from_bitslice::<O, u8>(bits);
-		self.advance(bits.len());
+	fn bits_free(&self) -> usize {
+		if self.overflowed {
+			0
+		} else {
+			self.bits_left().unwrap_or(usize::MAX)
+		}
 	}
 
-	pub fn write_bytes<B: AsRef<[u8]>>(&mut self, bytes: B) {
-		let bytes = bytes.as_ref();
-		let bits = bytes.view_bits();
-		self.bitvec.extend_from_bitslice::<O, u8>(bits);
-		self.advance(bits.len());
+	/// Returns `bits` zeroed bits at the cursor and moves the cursor past them, or returns `None` and sets the overflow
+	/// flag if they don't fit. The bits are inserted in insert mode and overwritten otherwise.
+	#[inline]
+	fn reserve(&mut self, bits: usize) -> Option<&mut BitSlice<O, u8>> {
+		let start = self.cursor;
+		let end = start + bits;
+		let len = self.bitvec.len();
+		let insert = self.insert && start < len;
+		let new_len = if insert { len + bits } else { len.max(end) };
+		if self.overflowed || matches!(self.max_bits, Some(max_bits) if new_len > max_bits) {
+			self.overflowed = true;
+			return None;
+		}
+		if new_len > len {
+			self.grow(new_len);
+		}
+		if insert {
+			self.bitvec.copy_within(start..len, end);
+		}
+		if start < len {
+			self.bitvec[start..end].set_all(false);
+		}
+		self.cursor = end;
+		Some(&mut self.bitvec[start..end])
 	}
 
-	pub fn write_float(&mut self, float: f32) {
-		let float = float.into_bitview();
-		let bits = float.view_bits();
-		self.bitvec
-			.extend_from_bitslice::<O, <<f32 as IsFloat>::Raw as BitView>::Store>(bits);
-		self.advance(bits.len());
+	/// Grows the bitvec to `new_len` bits, which are zeroed. This goes through the bytes underneath, as
+	/// [`BitVec::resize`] pushes the new bits one at a time.
+	fn grow(&mut self, new_len: usize) {
+		let len = self.bitvec.len();
+		self.bitvec.force_align();
+		let mut bytes = std::mem::take(&mut self.bitvec).into_vec();
+		bytes.resize((new_len + 7) / 8, 0);
+		self.bitvec = BitVec::from_vec(bytes);
+		self.bitvec.truncate(new_len);
+		// The bits past the old end in its last byte may have been left over from a truncation
+		self.bitvec[len..].set_all(false);
 	}
+}
 
-	#[cfg(target_pointer_width = "64")]
-	pub fn write_double(&mut self, double: f64) {
-		let double = double.into_bitview();
-		let bits = double.view_bits();
-		self.bitvec
-			.extend_from_bitslice::<O, <<f64 as IsFloat>::Raw as BitView>::Store>(bits);
-		self.advance(bits.len());
+impl<O: BitOrder> BitVecWriter<O>
+where
+	BitSlice<O, u8>: BitField + StoreBits<O>,
+{
+	/// Reserves `bits` zeroed bits at the cursor for an unsigned integer that isn't known yet, such as a length, to be
+	/// written later with [`fill`](BitVecWriter::fill).
+	///
+	/// If `bits` is wider than `N`, nothing is reserved and the overflow flag is set, as for a write that doesn't fit.
+	#[inline]
+	pub fn reserve_uint<N>(&mut self, bits: usize) -> Placeholder<N>
+	where
+		N: BitMemory + IsNumber + IsUnsigned,
+	{
+		let offset = if bits > N::BITS as usize {
+			self.overflowed = true;
+			None
+		} else {
+			let offset = self.cursor;
+			self.reserve(bits).map(|_| offset)
+		};
+		Placeholder {
+			offset,
+			bits,
+			ty: PhantomData,
+		}
 	}
 
-	pub fn write_string<S: AsRef<str>>(&mut self, str: S) {
-		let str = str.as_ref();
-		let bits = str.as_bits();
-		self.bitvec.extend_from_bitslice::<O, u8>(bits);
-		self.advance(bits.len());
+	/// Writes `uint` into the bits reserved by `placeholder`, without moving the cursor. Does nothing if the
+	/// reservation was refused because the writer overflowed.
+	///
+	/// If `uint` doesn't fit in the reserved bits, they are left as zeros and the overflow flag is set.
+	#[inline]
+	pub fn fill<N>(&mut self, placeholder: Placeholder<N>, uint: N)
+	where
+		N: BitMemory + IsNumber + IsUnsigned,
+	{
+		if let Some(offset) = placeholder.offset {
+			if N::BITS as usize - uint.leading_zeros() as usize > placeholder.bits {
+				self.overflowed = true;
+			} else if placeholder.bits != 0 {
+				self.bitvec[offset..offset + placeholder.bits].store_bits(uint);
+			}
+		}
 	}
 
-	pub fn write_string_nul<S: AsRef<str>>(&mut self, str: S) {
-		self.write_string(str);
-		self.write_byte(0);
+	/// Writes `value` with its [`ToBits`] implementation.
+	///
+	/// This shadows [`io::Write::write`], which can still be called as `io::Write::write(&mut writer, buf)`.
+	#[inline]
+	pub fn write<T: ToBits + ?Sized>(&mut self, value: &T) {
+		value.to_bits(self);
+	}
+
+	/// Writes whatever `write` writes, prefixed by its length in bits as a `bits`-bit unsigned integer.
+	///
+	/// If `bits` is more than 64, the length doesn't fit in `bits` bits, `write` leaves the cursor before where it
+	/// started, or the writer overflows, nothing is written and the overflow flag is set.
+	pub fn write_length_prefixed(&mut self, bits: usize, write: impl FnOnce(&mut BitVecWriter<O>)) {
+		if bits > 64 {
+			self.overflowed = true;
+			return;
+		}
+		let mut checkpoint = self.checkpoint();
+		let length = checkpoint.reserve_uint::<u64>(bits);
+		let start = checkpoint.cursor();
+		write(&mut checkpoint);
+		match checkpoint.cursor().checked_sub(start) {
+			Some(len) if !checkpoint.is_overflowed() && (bits == 64 || len as u64 >> bits == 0) => {
+				checkpoint.fill(length, len as u64);
+				checkpoint.commit();
+			}
+			_ => {
+				drop(checkpoint);
+				self.overflowed = true;
+			}
+		}
 	}
 }
 
-impl BitVecWriter<Msb0> {
-	pub fn write_int<N>(&mut self, int: N, bits: usize)
-	where
-		N: IsNumber + IsSigned + IntoBitView + BitCount,
+/// Bits reserved for an unsigned integer by [`BitVecWriter::reserve_uint`], to be filled in by
+/// [`BitVecWriter::fill`].
+///
+/// The placeholder refers to the bits by their offset, so inserting bits before it in
+/// [insert mode](BitVecWriter::set_insert_mode) leaves it pointing at the wrong bits.
+#[derive(Debug, PartialEq, Eq)]
+#[must_use = "the reserved bits stay zero unless the placeholder is filled"]
+pub struct Placeholder<N> {
+	/// The offset of the reserved bits, or `None` if the writer refused the reservation.
+	offset: Option<usize>,
+	bits: usize,
+	ty: PhantomData<N>,
+}
+impl<N> Placeholder<N> {
+	/// Returns the offset of the reserved bits, or `None` if the writer overflowed instead of reserving them.
+	#[inline]
+	pub fn offset(&self) -> Option<usize> {
+		self.offset
+	}
+
+	#[inline]
+	pub fn bits(&self) -> usize {
+		self.bits
+	}
+}
+
+/// A saved [`BitVecWriter`] position, returned by [`BitVecWriter::checkpoint`].
+///
+/// Dropping the guard restores the bits from the checkpoint's cursor onwards, the cursor and the overflow flag, unless
+/// [`commit`](WriterCheckpoint::commit) has been called. This drops a partly written update that didn't fit,
+/// leaving the writer free to write something smaller. Bits before the checkpoint's cursor that are overwritten
+/// through the guard aren't restored.
+#[derive(Debug)]
+#[must_use = "everything written is dropped as soon as the checkpoint is dropped"]
+pub struct WriterCheckpoint<'a, O: BitOrder> {
+	writer: &'a mut BitVecWriter<O>,
+	cursor: usize,
+	/// The bits from `tail_start`, which is the cursor or the end of the bitvec if that comes first, to the end.
+	tail_start: usize,
+	tail: BitVec<O, u8>,
+	overflowed: bool,
+	committed: bool,
+}
+impl<O: BitOrder> WriterCheckpoint<'_, O> {
+	/// Keeps everything written since the checkpoint.
+	#[inline]
+	pub fn commit(mut self) {
+		self.committed = true;
+	}
+
+	/// Drops everything written since the checkpoint now.
+	#[inline]
+	pub fn rollback(self) {}
+}
+impl<O: BitOrder> Deref for WriterCheckpoint<'_, O> {
+	type Target = BitVecWriter<O>;
+
+	#[inline]
+	fn deref(&self) -> &BitVecWriter<O> {
+		self.writer
+	}
+}
+impl<O: BitOrder> DerefMut for WriterCheckpoint<'_, O> {
+	#[inline]
+	fn deref_mut(&mut self) -> &mut BitVecWriter<O> {
+		self.writer
+	}
+}
+impl<O: BitOrder> Drop for WriterCheckpoint<'_, O> {
+	#[inline]
+	fn drop(&mut self) {
+		if !self.committed {
+			let bitvec = &mut self.writer.bitvec;
+			bitvec.truncate(self.tail_start);
+			bitvec.extend_from_bitslice(&self.tail);
+			self.writer.cursor = self.cursor;
+			self.writer.overflowed = self.overflowed;
+		}
+	}
+}
+
+/// Writes bits into a borrowed [`BitSlice`], such as a preallocated send buffer, without allocating.
+///
+/// This has the same write API as [`BitVecWriter`]. A write that doesn't fit in the rest of the slice writes
+/// nothing and sets the same sticky overflow flag as a [`BitVecWriter`] with a [`max_bits`](BitVecWriter::max_bits).
+#[derive(Debug)]
+pub struct BitSliceWriter<'a, O: BitOrder> {
+	cursor: usize,
+	overflowed: bool,
+	pub bitslice: &'a mut BitSlice<O, u8>,
+}
+impl<'a, O: BitOrder> BitSliceWriter<'a, O> {
+	#[inline]
+	pub fn new(bitslice: &'a mut BitSlice<O, u8>) -> BitSliceWriter<'a, O> {
+		BitSliceWriter {
+			cursor: 0,
+			overflowed: false,
+			bitslice,
+		}
+	}
+
+	#[inline]
+	pub fn from_bytes(bytes: &'a mut [u8]) -> BitSliceWriter<'a, O> {
+		BitSliceWriter::new(BitSlice::from_slice_mut(bytes).unwrap())
+	}
+
+	#[inline]
+	pub fn into_bitslice(self) -> &'a mut BitSlice<O, u8> {
+		self.bitslice
+	}
+
+	/// Returns the bits written so far.
+	#[inline]
+	pub fn written(&self) -> &BitSlice<O, u8> {
+		&self.bitslice[..self.cursor]
+	}
+
+	/// Returns the bytes the bits written so far are in, ready to be sent.
+	#[inline]
+	pub fn written_bytes(&self) -> &[u8] {
+		self.written().as_raw_slice()
+	}
+
+	/// Returns where the bits written end, which is always the cursor as it only moves forward.
+	#[inline]
+	pub fn bits_written(&self) -> usize {
+		self.cursor
+	}
+
+	/// Returns the number of bytes written to the slice, rounded up to the byte.
+	#[inline]
+	pub fn bytes_written(&self) -> usize {
+		(self.bits_written() + 7) / 8
+	}
+
+	/// Returns the length of the slice, which is the most bits that can be written.
+	#[inline]
+	pub fn max_bits(&self) -> usize {
+		self.bitslice.len()
+	}
+
+	/// Returns the number of bits that can still be written before the writer overflows, which is always `Some`
+	/// as the writer can't grow.
+	#[inline]
+	pub fn bits_left(&self) -> Option<usize> {
+		Some(self.bitslice.len() - self.cursor)
+	}
+
+	/// Returns whether a write has been refused because it didn't fit in the slice.
+	#[inline]
+	pub fn is_overflowed(&self) -> bool {
+		self.overflowed
+	}
+
+	#[inline]
+	pub fn cursor(&self) -> usize {
+		self.cursor
+	}
+
+	/// Sets the bit at the given index. Will panic if the index is out of bounds.
+	#[inline]
+	pub fn set_bit(&mut self, index: usize, bit: bool) {
+		self.bitslice.set(index, bit);
+	}
+
+	/// Sets the bits at the given index. Will panic if the index is out of bounds.
+	#[inline]
+	pub fn set_bits<T: BitStore>(&mut self, index: usize, bits: &BitSlice<O, T>) {
+		for (i, bit) in bits.iter().enumerate() {
+			self.bitslice.set(index + i, *bit);
+		}
+	}
+
+	/// Returns the number of bits [`reserve`](Self::reserve) can still hand out.
+	#[inline]
+	fn bits_free(&self) -> usize {
+		if self.overflowed {
+			0
+		} else {
+			self.bitslice.len() - self.cursor
+		}
+	}
+
+	/// Zeroes the next `bits` bits and returns them, or returns `None` and sets the overflow flag if they don't fit.
+	#[inline]
+	fn reserve(&mut self, bits: usize) -> Option<&mut BitSlice<O, u8>> {
+		let start = self.cursor;
+		if self.overflowed || start + bits > self.bitslice.len() {
+			self.overflowed = true;
+			return None;
+		}
+		self.cursor += bits;
+		let bits = &mut self.bitslice[start..start + bits];
+		bits.set_all(false);
+		Some(bits)
+	}
+}
+
+/// Writes bits into a fixed-size byte array stored inline, so building a message never allocates.
+///
+/// This has the same write API as [`BitVecWriter`], and overflows like a [`BitSliceWriter`] once `BYTES` bytes
+/// have been written. The written bits can be read back or sent without copying with
+/// [`as_reader`](ArrayBitWriter::as_reader) and [`as_bytes`](ArrayBitWriter::as_bytes).
+#[derive(Debug, Clone)]
+pub struct ArrayBitWriter<O: BitOrder, const BYTES: usize> {
+	cursor: usize,
+	overflowed: bool,
+	bytes: [u8; BYTES],
+	order: PhantomData<O>,
+}
+impl<O: BitOrder, const BYTES: usize> ArrayBitWriter<O, BYTES> {
+	#[inline]
+	pub fn new() -> ArrayBitWriter<O, BYTES> {
+		ArrayBitWriter {
+			cursor: 0,
+			overflowed: false,
+			bytes: [0; BYTES],
+			order: PhantomData,
+		}
+	}
+
+	/// Returns the whole array, including any bytes that haven't been written to.
+	#[inline]
+	pub fn into_array(self) -> [u8; BYTES] {
+		self.bytes
+	}
+
+	/// Returns the bits written so far.
+	#[inline]
+	pub fn written(&self) -> &BitSlice<O, u8> {
+		&BitSlice::from_slice(&self.bytes).unwrap()[..self.cursor]
+	}
+
+	/// Returns the bytes written so far, rounded up to the byte.
+	#[inline]
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.bytes[..self.bytes_written()]
+	}
+
+	/// Returns a reader over the bits written so far.
+	#[inline]
+	pub fn as_reader(&self) -> BitSliceReader<'_, O> {
+		BitSliceReader {
+			cursor: 0,
+			bitslice: self.written(),
+		}
+	}
+
+	/// Returns where the bits written end, which is always the cursor as it only moves forward.
+	#[inline]
+	pub fn bits_written(&self) -> usize {
+		self.cursor
+	}
+
+	/// Returns the number of bytes written to the array, rounded up to the byte.
+	#[inline]
+	pub fn bytes_written(&self) -> usize {
+		(self.bits_written() + 7) / 8
+	}
+
+	/// Returns the size of the array in bits, which is the most bits that can be written.
+	#[inline]
+	pub fn max_bits(&self) -> usize {
+		BYTES * 8
+	}
+
+	/// Returns the number of bits that can still be written before the writer overflows, which is always `Some`
+	/// as the writer can't grow.
+	#[inline]
+	pub fn bits_left(&self) -> Option<usize> {
+		Some(BYTES * 8 - self.cursor)
+	}
+
+	/// Returns whether a write has been refused because it didn't fit in the array.
+	#[inline]
+	pub fn is_overflowed(&self) -> bool {
+		self.overflowed
+	}
+
+	#[inline]
+	pub fn cursor(&self) -> usize {
+		self.cursor
+	}
+
+	/// Sets the bit at the given index. Will panic if the index is out of bounds.
+	#[inline]
+	pub fn set_bit(&mut self, index: usize, bit: bool) {
+		self.bitslice_mut().set(index, bit);
+	}
+
+	/// Sets the bits at the given index. Will panic if the index is out of bounds.
+	#[inline]
+	pub fn set_bits<T: BitStore>(&mut self, index: usize, bits: &BitSlice<O, T>) {
+		let bitslice = self.bitslice_mut();
+		for (i, bit) in bits.iter().enumerate() {
+			bitslice.set(index + i, *bit);
+		}
+	}
+
+	#[inline]
+	fn bitslice_mut(&mut self) -> &mut BitSlice<O, u8> {
+		BitSlice::from_slice_mut(&mut self.bytes).unwrap()
+	}
+
+	/// Returns the number of bits [`reserve`](Self::reserve) can still hand out.
+	#[inline]
+	fn bits_free(&self) -> usize {
+		if self.overflowed {
+			0
+		} else {
+			BYTES * 8 - self.cursor
+		}
+	}
+
+	/// Zeroes the next `bits` bits and returns them, or returns `None` and sets the overflow flag if they don't fit.
+	#[inline]
+	fn reserve(&mut self, bits: usize) -> Option<&mut BitSlice<O, u8>> {
+		let start = self.cursor;
+		if self.overflowed || start + bits > BYTES * 8 {
+			self.overflowed = true;
+			return None;
+		}
+		self.cursor += bits;
+		let bits = &mut self.bitslice_mut()[start..start + bits];
+		bits.set_all(false);
+		Some(bits)
+	}
+}
+impl<O: BitOrder, const BYTES: usize> Default for ArrayBitWriter<O, BYTES> {
+	#[inline]
+	fn default() -> Self {
+		ArrayBitWriter::new()
+	}
+}
+
+/// Stores `bytes` into `bits`, which must be `bytes.len() * 8` bits long, copying them straight into the underlying
+/// memory if `bits` starts and ends on byte boundaries.
+#[inline]
+fn store_bytes<O: BitOrder>(bits: &mut BitSlice<O, u8>, bytes: &[u8])
+where
+	BitSlice<O, u8>: BitField + StoreBits<O>,
+{
+	if let DomainMut::Region {
+		head: None,
+		body,
+		tail: None,
+	} = bits.domain_mut()
 	{
-		let int = int.into_bitview();
-		let bits = &int.view_bits()[(N::BIT_COUNT - bits)..];
-		self.bitvec
-			.extend_from_bitslice::<Msb0, <<N as IntoBitView>::Unsigned as BitView>::Store>(bits);
-		self.advance(bits.len());
+		body.copy_from_slice(bytes);
+		return;
+	}
+	for (i, byte) in bytes.iter().enumerate() {
+		bits[i * 8..i * 8 + 8].store_bits(*byte);
+	}
+}
+
+/// The writes shared by every writer and the [`BitWrite`] defaults, which are built on `write_bit`, `write_uint`,
+/// `write_bytes` and the varint writer `$write_varint`.
+macro_rules! shared_writes {
+	( $vis:vis, $write_varint:path ) => {
+		/// Writes a protobuf-style varint of at most 5 bytes.
+		#[inline]
+		$vis fn write_varint32(&mut self, varint: u32) {
+			$write_varint(self, varint as u64);
+		}
+
+		/// Writes a protobuf-style varint of at most 10 bytes.
+		#[inline]
+		$vis fn write_varint64(&mut self, varint: u64) {
+			$write_varint(self, varint);
+		}
+
+		/// Writes a zigzag-encoded [`write_varint32`](Self::write_varint32).
+		#[inline]
+		$vis fn write_signed_varint32(&mut self, varint: i32) {
+			self.write_varint32(zigzag_encode32(varint));
+		}
+
+		/// Writes a zigzag-encoded [`write_varint64`](Self::write_varint64).
+		#[inline]
+		$vis fn write_signed_varint64(&mut self, varint: i64) {
+			self.write_varint64(zigzag_encode64(varint));
+		}
+
+		/// Writes a Source engine `UBitVar`: a 2-bit selector followed by a 4, 8, 12 or 32-bit value.
+		$vis fn write_ubitvar(&mut self, uint: u32) {
+			let (selector, bits) = if uint & 0xF == uint {
+				(0_u8, 4)
+			} else if uint & 0xFF == uint {
+				(1, 8)
+			} else if uint & 0xFFF == uint {
+				(2, 12)
+			} else {
+				(3, 32)
+			};
+			self.write_uint(selector, 2);
+			self.write_uint(uint, bits);
+		}
+
+		/// Writes a Source engine entity property index, delta-encoded against `last_index`, or the end marker if
+		/// `index` is `None`.
+		///
+		/// `new_way` selects the encoding with the extra short forms for small deltas. Will panic if `index` is not
+		/// greater than `last_index`, or is more than 4095 greater.
+		$vis fn write_field_index(&mut self, last_index: i32, index: Option<i32>, new_way: bool) {
+			let delta = match index {
+				Some(index) => match index.checked_sub(last_index) {
+					Some(delta) if delta > 0 && delta <= FIELD_INDEX_END as i32 => (delta - 1) as u32,
+					_ => panic!(
+						"field index {} can't be delta-encoded against {}",
+						index, last_index
+					),
+				},
+				None => FIELD_INDEX_END,
+			};
+			if new_way {
+				if delta == 0 {
+					self.write_bit(true);
+					return;
+				}
+				self.write_bit(false);
+				if delta < 8 {
+					self.write_bit(true);
+					self.write_uint(delta, 3);
+					return;
+				}
+				self.write_bit(false);
+			}
+			if delta < 32 {
+				self.write_uint(delta, 7);
+			} else if delta < 128 {
+				self.write_uint((delta & 31) | 32, 7);
+				self.write_uint(delta >> 5, 2);
+			} else if delta < 512 {
+				self.write_uint((delta & 31) | 64, 7);
+				self.write_uint(delta >> 5, 4);
+			} else {
+				self.write_uint((delta & 31) | 96, 7);
+				self.write_uint(delta >> 5, 7);
+			}
+		}
+
+		/// Writes a coordinate with Source engine's `WriteBitCoord` encoding.
+		$vis fn write_bit_coord(&mut self, coord: f32) {
+			let sign = (coord as f64) <= -COORD_RESOLUTION;
+			let int = coord.abs() as u32;
+			let fract =
+				((coord * COORD_DENOMINATOR as f32) as i32).unsigned_abs() & (COORD_DENOMINATOR - 1);
+
+			self.write_bit(int != 0);
+			self.write_bit(fract != 0);
+			if int != 0 || fract != 0 {
+				self.write_bit(sign);
+				if int != 0 {
+					self.write_uint(int - 1, COORD_INTEGER_BITS);
+				}
+				if fract != 0 {
+					self.write_uint(fract, COORD_FRACTIONAL_BITS);
+				}
+			}
+		}
+
+		/// Writes a coordinate with Source engine's `WriteBitCoordMP` encoding.
+		///
+		/// `integral` drops the fractional part entirely, and `low_precision` writes it with 3 bits instead of 5.
+		$vis fn write_bit_coord_mp(&mut self, coord: f32, integral: bool, low_precision: bool) {
+			let (resolution, denominator, fract_bits) = if low_precision {
+				(
+					COORD_RESOLUTION_LOWPRECISION,
+					COORD_DENOMINATOR_LOWPRECISION,
+					COORD_FRACTIONAL_BITS_MP_LOWPRECISION,
+				)
+			} else {
+				(COORD_RESOLUTION, COORD_DENOMINATOR, COORD_FRACTIONAL_BITS)
+			};
+			let sign = (coord as f64) <= -resolution;
+			let int = coord.abs() as u32;
+			let fract = ((coord * denominator as f32) as i32).unsigned_abs() & (denominator - 1);
+			let in_bounds = int < (1 << COORD_INTEGER_BITS_MP);
+			let int_bits = if in_bounds {
+				COORD_INTEGER_BITS_MP
+			} else {
+				COORD_INTEGER_BITS
+			};
+
+			self.write_bit(in_bounds);
+			self.write_bit(int != 0);
+			if integral {
+				if int != 0 {
+					self.write_bit(sign);
+					self.write_uint(int - 1, int_bits);
+				}
+			} else {
+				self.write_bit(sign);
+				if int != 0 {
+					self.write_uint(int - 1, int_bits);
+				}
+				self.write_uint(fract, fract_bits);
+			}
+		}
+
+		/// Writes a normal component with Source engine's `WriteBitNormal` encoding.
+		$vis fn write_bit_normal(&mut self, normal: f32) {
+			let sign = (normal as f64) <= -NORMAL_RESOLUTION;
+			let fract = ((normal * NORMAL_DENOMINATOR as f32) as i32)
+				.unsigned_abs()
+				.min(NORMAL_DENOMINATOR);
+
+			self.write_bit(sign);
+			self.write_uint(fract, NORMAL_FRACTIONAL_BITS);
+		}
+
+		/// Writes an angle in degrees with Source engine's `WriteBitAngle` encoding, using `bits` bits. Will panic if
+		/// `bits` isn't in `1..=32`.
+		$vis fn write_bit_angle(&mut self, angle: f32, bits: usize) {
+			assert!(bits > 0 && bits <= 32, "bit angles can't be {} bits wide", bits);
+			let shift = 1_u64 << bits;
+			let angle = ((angle as f64 / 360.0) * shift as f64) as i32 as u32 & (shift - 1) as u32;
+			self.write_uint(angle, bits);
+		}
+
+		/// Writes a vector with Source engine's `WriteBitVec3Coord` encoding.
+		$vis fn write_bit_vec3_coord(&mut self, vec: [f32; 3]) {
+			let flags = vec.map(|component| {
+				component as f64 >= COORD_RESOLUTION || component as f64 <= -COORD_RESOLUTION
+			});
+			for flag in flags {
+				self.write_bit(flag);
+			}
+			for (component, flag) in vec.iter().zip(flags) {
+				if flag {
+					self.write_bit_coord(*component);
+				}
+			}
+		}
+
+		/// Writes a unit vector with Source engine's `WriteBitVec3Normal` encoding.
+		///
+		/// Only the sign of the z component is written; the reader derives its magnitude from x and y.
+		$vis fn write_bit_vec3_normal(&mut self, vec: [f32; 3]) {
+			let [x, y, z] = vec;
+			let has_x = x as f64 >= NORMAL_RESOLUTION || x as f64 <= -NORMAL_RESOLUTION;
+			let has_y = y as f64 >= NORMAL_RESOLUTION || y as f64 <= -NORMAL_RESOLUTION;
+
+			self.write_bit(has_x);
+			self.write_bit(has_y);
+			if has_x {
+				self.write_bit_normal(x);
+			}
+			if has_y {
+				self.write_bit_normal(y);
+			}
+			self.write_bit(z as f64 <= -NORMAL_RESOLUTION);
+		}
+
+		$vis fn write_string<S: AsRef<str>>(&mut self, str: S) {
+			self.write_bytes(str.as_ref());
+		}
+	};
+}
+
+/// Writes bits, so that an encoder can be written once and run on any writer: the crate's writers into owned
+/// buffers, borrowed slices, arrays and streams, or a test double.
+///
+/// Only [`bits_written`](BitWrite::bits_written), [`write_bit`](BitWrite::write_bit) and
+/// [`write_uint`](BitWrite::write_uint) have to be implemented. Every other write has a default built on those, which
+/// the crate's writers override with their own.
+pub trait BitWrite {
+	/// Returns where the bits written end, which is the length of the output so far.
+	///
+	/// This is where the next write goes, unless the writer can move its cursor back like
+	/// [`BitVecWriter::seek`] does; the cursor doesn't change it.
+	fn bits_written(&self) -> usize;
+
+	/// Returns the number of bits that can still be written before the writer overflows, or `None` if it has no limit.
+	#[inline]
+	fn bits_left(&self) -> Option<usize> {
+		None
+	}
+
+	/// Returns the number of bytes written, rounded up to the byte.
+	#[inline]
+	fn bytes_written(&self) -> usize {
+		(self.bits_written() + 7) / 8
 	}
 
-	pub fn write_uint<N>(&mut self, uint: N, bits: usize)
+	fn write_bit(&mut self, bit: bool);
+
+	fn write_uint<N>(&mut self, uint: N, bits: usize)
+	where
+		N: BitMemory + IsNumber + IsUnsigned;
+
+	/// Writes `value` with its [`ToBits`] implementation.
+	#[inline]
+	fn write<T: ToBits + ?Sized>(&mut self, value: &T)
 	where
-		N: BitView + IsNumber + IsUnsigned + BitCount,
+		Self: Sized,
 	{
-		let bits = &uint.view_bits()[(N::BIT_COUNT - bits)..];
-		self.bitvec.extend_from_bitslice::<Msb0, N::Store>(bits);
-		self.advance(bits.len());
+		value.to_bits(self);
 	}
 
-	#[cfg(target_pointer_width = "32")]
-	pub fn write_double(&mut self, double: f64) {
-		// Split the f64 into two u32s
-		const MASK_64_32: u64 = 0x00000000FFFFFFFF;
-		let double: u64 = unsafe { std::mem::transmute(double) };
-		let double1: u32 = ((double >> 32) & MASK_64_32) as u32;
-		let double2: u32 = (double & MASK_64_32) as u32;
-		self.write_uint(double1, 32);
-		self.write_uint(double2, 32);
+	#[inline]
+	fn write_byte(&mut self, byte: u8) {
+		self.write_uint(byte, 8);
 	}
-}
-impl BitVecWriter<Lsb0> {
-	pub fn write_int<N>(&mut self, int: N, bits: usize)
+
+	fn write_bytes<B: AsRef<[u8]>>(&mut self, bytes: B) {
+		for byte in bytes.as_ref() {
+			self.write_byte(*byte);
+		}
+	}
+
+	/// Writes the low `bits` bits of each of `uints` one after the other, for `bits` in `1..=32`.
+	fn write_uints(&mut self, uints: &[u32], bits: usize) {
+		for uint in uints {
+			self.write_uint(*uint, bits);
+		}
+	}
+
+	#[inline]
+	fn write_int<N>(&mut self, int: N, bits: usize)
 	where
 		N: IsNumber + IsSigned + IntoBitView,
 	{
-		let int = int.into_bitview();
-		let bits = &int.view_bits()[..bits];
-		self.bitvec
-			.extend_from_bitslice::<Lsb0, <<N as IntoBitView>::Unsigned as BitView>::Store>(bits);
-		self.advance(bits.len());
+		self.write_uint(int.into_bitview(), bits);
 	}
 
-	pub fn write_uint<N>(&mut self, uint: N, bits: usize)
-	where
-		N: BitView + IsNumber + IsUnsigned,
-	{
-		let bits = &uint.view_bits()[..bits];
-		self.bitvec.extend_from_bitslice::<Lsb0, N::Store>(bits);
-		self.advance(bits.len());
+	#[inline]
+	fn write_float(&mut self, float: f32) {
+		self.write_uint(float.to_bits(), f32::BIT_COUNT);
 	}
 
-	#[cfg(target_pointer_width = "32")]
-	pub fn write_double(&mut self, double: f64) {
-		// Split the f64 into two u32s
-		const MASK_64_32: u64 = 0x00000000FFFFFFFF;
-		let double: u64 = unsafe { std::mem::transmute(double) };
-		let double1: u32 = ((double >> 32) & MASK_64_32) as u32;
-		let double2: u32 = (double & MASK_64_32) as u32;
-		self.write_uint(double2, 32); // SWAPPED ORDER
-		self.write_uint(double1, 32);
+	#[inline]
+	fn write_double(&mut self, double: f64) {
+		self.write_uint(double.to_bits(), f64::BIT_COUNT);
+	}
+
+	shared_writes!(, write_varint);
+
+	fn write_string_nul<S: AsRef<str>>(&mut self, str: S) {
+		self.write_string(str);
+		self.write_byte(0);
 	}
 }
 
-impl<O: BitOrder> Into<Vec<u8>> for BitVecWriter<O> {
-	fn into(self) -> Vec<u8> {
-		self.into_bytes()
+/// Writes a varint a byte at a time, for the [`BitWrite`] defaults.
+fn write_varint<W: BitWrite + ?Sized>(writer: &mut W, mut varint: u64) {
+	while varint > 0x7F {
+		writer.write_byte((varint & 0x7F) as u8 | 0x80);
+		varint >>= 7;
+	}
+	writer.write_byte(varint as u8);
+}
+
+/// Implements the [`BitWrite`] methods that every writer in this crate has its own version of, by calling it. With no
+/// arguments, forwards all of them.
+macro_rules! forward_bit_write {
+	() => {
+		#[inline]
+		fn bits_written(&self) -> usize {
+			Self::bits_written(self)
+		}
+
+		#[inline]
+		fn write_uint<N>(&mut self, uint: N, bits: usize)
+		where
+			N: BitMemory + IsNumber + IsUnsigned,
+		{
+			Self::write_uint(self, uint, bits)
+		}
+
+		#[inline]
+		fn write_int<N>(&mut self, int: N, bits: usize)
+		where
+			N: IsNumber + IsSigned + IntoBitView,
+		{
+			Self::write_int(self, int, bits)
+		}
+
+		#[inline]
+		fn write_bytes<B: AsRef<[u8]>>(&mut self, bytes: B) {
+			Self::write_bytes(self, bytes)
+		}
+
+		#[inline]
+		fn write_string<S: AsRef<str>>(&mut self, str: S) {
+			Self::write_string(self, str)
+		}
+
+		#[inline]
+		fn write_string_nul<S: AsRef<str>>(&mut self, str: S) {
+			Self::write_string_nul(self, str)
+		}
+
+		forward_bit_write! {
+			fn write_bit(&mut self, bit: bool);
+			fn write_byte(&mut self, byte: u8);
+			fn write_uints(&mut self, uints: &[u32], bits: usize);
+			fn write_float(&mut self, float: f32);
+			#[cfg(target_pointer_width = "64")]
+			fn write_double(&mut self, double: f64);
+			fn write_varint32(&mut self, varint: u32);
+			fn write_varint64(&mut self, varint: u64);
+			fn write_signed_varint32(&mut self, varint: i32);
+			fn write_signed_varint64(&mut self, varint: i64);
+			fn write_ubitvar(&mut self, uint: u32);
+			fn write_field_index(&mut self, last_index: i32, index: Option<i32>, new_way: bool);
+			fn write_bit_coord(&mut self, coord: f32);
+			fn write_bit_coord_mp(&mut self, coord: f32, integral: bool, low_precision: bool);
+			fn write_bit_normal(&mut self, normal: f32);
+			fn write_bit_angle(&mut self, angle: f32, bits: usize);
+			fn write_bit_vec3_coord(&mut self, vec: [f32; 3]);
+			fn write_bit_vec3_normal(&mut self, vec: [f32; 3]);
+		}
+	};
+	( $( $(#[$attr:meta])* fn $name:ident(&mut self $(, $arg:ident: $ty:ty)*); )* ) => {$(
+		$(#[$attr])*
+		#[inline]
+		fn $name(&mut self $(, $arg: $ty)*) {
+			Self::$name(self $(, $arg)*)
+		}
+	)*};
+}
+pub(crate) use forward_bit_write;
+
+/// The `write_*` API and [`io::Write`] impl shared by [`BitVecWriter`], [`BitSliceWriter`] and [`ArrayBitWriter`],
+/// built on their `reserve`.
+macro_rules! impl_writer {
+	( impl<$($lt:lifetime,)? O $(, const $n:ident: usize)?> $writer:ty, $lsb:ty, $msb:ty ) => {
+		impl<$($lt,)? O: BitOrder $(, const $n: usize)?> $writer
+		where
+			BitSlice<O, u8>: BitField + StoreBits<O>,
+		{
+			#[inline]
+			pub fn write_bit(&mut self, bit: bool) {
+				if let Some(bits) = self.reserve(1) {
+					bits.set(0, bit);
+				}
+			}
+
+			#[inline]
+			pub fn write_byte(&mut self, byte: u8) {
+				if let Some(bits) = self.reserve(8) {
+					bits.store_bits(byte);
+				}
+			}
+
+			pub fn write_bytes<B: AsRef<[u8]>>(&mut self, bytes: B) {
+				let bytes = bytes.as_ref();
+				if let Some(bits) = self.reserve(bytes.len() * 8) {
+					store_bytes(bits, bytes);
+				}
+			}
+
+			pub fn write_uint<N>(&mut self, uint: N, bits: usize)
+			where
+				N: BitMemory + IsNumber + IsUnsigned,
+			{
+				if bits == 0 {
+					return;
+				}
+				if let Some(bits) = self.reserve(bits) {
+					bits.store_bits(uint);
+				}
+			}
+
+			/// Writes the low `bits` bits of each of `uints` one after the other, for `bits` in `1..=32`.
+			///
+			/// This is much faster than calling [`write_uint`](Self::write_uint) for each of them, as several uints
+			/// are stored at once.
+			pub fn write_uints(&mut self, uints: &[u32], bits: usize) {
+				if bits == 0 {
+					return;
+				}
+				if let Some(slice) = self.reserve(uints.len() * bits) {
+					slice.store_uints(uints, bits);
+				}
+			}
+
+			pub fn write_int<N>(&mut self, int: N, bits: usize)
+			where
+				N: IsNumber + IsSigned + IntoBitView,
+			{
+				if bits == 0 {
+					return;
+				}
+				if let Some(bits) = self.reserve(bits) {
+					bits.store_bits(int.into_bitview());
+				}
+			}
+
+			pub fn write_float(&mut self, float: f32) {
+				if let Some(bits) = self.reserve(f32::BIT_COUNT) {
+					bits.store_bits(float.into_bitview());
+				}
+			}
+
+			#[cfg(target_pointer_width = "64")]
+			pub fn write_double(&mut self, double: f64) {
+				if let Some(bits) = self.reserve(f64::BIT_COUNT) {
+					bits.store_bits(double.into_bitview());
+				}
+			}
+
+			shared_writes!(pub, Self::write_varint);
+
+			fn write_varint(&mut self, varint: u64) {
+				let len = (64 - (varint | 1).leading_zeros() as usize + 6) / 7;
+				if let Some(bits) = self.reserve(len * 8) {
+					for i in 0..len {
+						let mut byte = ((varint >> (7 * i)) & 0x7F) as u8;
+						if i + 1 < len {
+							byte |= 0x80;
+						}
+						bits[i * 8..i * 8 + 8].store_bits(byte);
+					}
+				}
+			}
+
+			pub fn write_string_nul<S: AsRef<str>>(&mut self, str: S) {
+				let str = str.as_ref();
+				if let Some(bits) = self.reserve((str.len() + 1) * 8) {
+					store_bytes(&mut bits[..str.len() * 8], str.as_bytes());
+				}
+			}
+		}
+
+		impl<$($lt,)? O: BitOrder $(, const $n: usize)?> BitWrite for $writer
+		where
+			BitSlice<O, u8>: BitField + StoreBits<O>,
+		{
+			#[inline]
+			fn bits_left(&self) -> Option<usize> {
+				Self::bits_left(self)
+			}
+
+			forward_bit_write!();
+		}
+
+	impl<$($lt)? $(const $n: usize)?> $msb {
+		#[cfg(target_pointer_width = "32")]
+		pub fn write_double(&mut self, double: f64) {
+			// Split the f64 into two u32s
+			const MASK_64_32: u64 = 0x00000000FFFFFFFF;
+			let double = double.to_bits();
+			let double1: u32 = ((double >> 32) & MASK_64_32) as u32;
+			let double2: u32 = (double & MASK_64_32) as u32;
+			if let Some(bits) = self.reserve(64) {
+				bits[..32].store_bits(double1);
+				bits[32..].store_bits(double2);
+			}
+		}
+	}
+	impl<$($lt)? $(const $n: usize)?> $lsb {
+		#[cfg(target_pointer_width = "32")]
+		pub fn write_double(&mut self, double: f64) {
+			// Split the f64 into two u32s
+			const MASK_64_32: u64 = 0x00000000FFFFFFFF;
+			let double = double.to_bits();
+			let double1: u32 = ((double >> 32) & MASK_64_32) as u32;
+			let double2: u32 = (double & MASK_64_32) as u32;
+			if let Some(bits) = self.reserve(64) {
+				bits[..32].store_bits(double2); // SWAPPED ORDER
+				bits[32..].store_bits(double1);
+			}
+		}
+	}
+
+		/// Writes bytes at the cursor, even if it isn't byte-aligned. A bounded writer accepts as many whole bytes
+		/// as fit, and only overflows once a write can't fit a single byte.
+		impl<$($lt,)? O: BitOrder $(, const $n: usize)?> io::Write for $writer
+		where
+			BitSlice<O, u8>: BitField + StoreBits<O>,
+		{
+			fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+				let len = buf.len().min(self.bits_free() / 8);
+				if len == 0 && !buf.is_empty() {
+					self.overflowed = true;
+				}
+				self.write_bytes(&buf[..len]);
+				Ok(len)
+			}
+
+			#[inline]
+			fn flush(&mut self) -> io::Result<()> {
+				Ok(())
+			}
+		}
+	};
+}
+impl_writer!(impl<O> BitVecWriter<O>, BitVecWriter<Lsb0>, BitVecWriter<Msb0>);
+impl_writer!(impl<'a, O> BitSliceWriter<'a, O>, BitSliceWriter<'a, Lsb0>, BitSliceWriter<'a, Msb0>);
+impl_writer!(impl<O, const BYTES: usize> ArrayBitWriter<O, BYTES>, ArrayBitWriter<Lsb0, BYTES>, ArrayBitWriter<Msb0, BYTES>);
+
+#[inline]
+fn zigzag_encode32(n: i32) -> u32 {
+	((n << 1) ^ (n >> 31)) as u32
+}
+
+#[inline]
+fn zigzag_encode64(n: i64) -> u64 {
+	((n << 1) ^ (n >> 63)) as u64
+}
+
+impl<O: BitOrder> From<BitVecWriter<O>> for Vec<u8> {
+	fn from(writer: BitVecWriter<O>) -> Self {
+		writer.into_bytes()
 	}
 }
 impl<O: BitOrder> From<Vec<u8>> for BitVecWriter<O> {
 	fn from(bytes: Vec<u8>) -> Self {
-		BitVecWriter::from_bytes(bytes)
+		BitVecWriter::new(BitVec::from_vec(bytes))
 	}
 }
 impl<O: BitOrder> From<&[u8]> for BitVecWriter<O> {
 	fn from(bytes: &[u8]) -> Self {
-		BitVecWriter::from_byte_slice(bytes)
+		BitVecWriter::new(BitVec::from_slice(bytes).unwrap())
+	}
+}
+impl<'a, O: BitOrder> From<&'a mut [u8]> for BitSliceWriter<'a, O> {
+	fn from(bytes: &'a mut [u8]) -> Self {
+		BitSliceWriter::from_bytes(bytes)
+	}
+}
+
+pub trait StoreBits<O: BitOrder>
+where
+	BitSlice<O, u8>: BitField,
+{
+	fn store_bits<M: BitMemory>(&mut self, value: M);
+
+	/// Stores the low `bits` bits of each of `uints` one after the other, for `bits` in `1..=32`. `self` must be
+	/// `uints.len() * bits` bits long.
+	fn store_uints(&mut self, uints: &[u32], bits: usize);
+}
+impl<O: BitOrder> StoreBits<O> for BitSlice<Lsb0, u8>
+where
+	BitSlice<O, u8>: BitField,
+{
+	fn store_bits<M: BitMemory>(&mut self, value: M) {
+		self.store_le(value)
+	}
+
+	fn store_uints(&mut self, uints: &[u32], bits: usize) {
+		pack_uints::<Lsb0>(self, uints, bits)
+	}
+}
+impl<O: BitOrder> StoreBits<O> for BitSlice<Msb0, u8>
+where
+	BitSlice<O, u8>: BitField,
+{
+	fn store_bits<M: BitMemory>(&mut self, value: M) {
+		self.store_be(value)
+	}
+
+	fn store_uints(&mut self, uints: &[u32], bits: usize) {
+		pack_uints::<Msb0>(self, uints, bits)
 	}
 }
 
+/// Stores `bits`-bit uints into `slice` by packing them into bytes lined up with the memory under it, through a
+/// cache laid out like a [`CachedBitReader`](crate::CachedBitReader)'s, and copying the bytes over.
+fn pack_uints<O: CacheOrder>(slice: &mut BitSlice<O, u8>, uints: &[u32], bits: usize)
+where
+	BitSlice<O, u8>: BitField + StoreBits<O>,
+{
+	if uints.is_empty() {
+		return;
+	}
+	let (_, head) = slice.as_bitptr().raw_parts();
+	let head = head.into_inner() as usize;
+	let mut bytes = Vec::with_capacity((head + slice.len() + 7) / 8);
+	let (mut cache, mut cached) = (0, head as u32);
+	specialize_width!(bits as u32, |bits| {
+		for uint in uints {
+			cache |= O::place(O::put(*uint as u64, bits), cached);
+			cached += bits;
+			while cached >= 8 {
+				bytes.push(O::peek(cache, 8) as u8);
+				cache = O::consume(cache, 8);
+				cached -= 8;
+			}
+		}
+	});
+	if cached > 0 {
+		bytes.push(O::peek(cache, 8) as u8);
+	}
+
+	// Only the bits sharing a byte with bits outside `slice` need to be set one by one
+	let packed = &BitSlice::<O, u8>::from_slice(&bytes).unwrap()[head..];
+	let first = ((8 - head) % 8).min(slice.len());
+	let whole = (slice.len() - first) / 8 * 8;
+	for i in (0..first).chain(first + whole..slice.len()) {
+		slice.set(i, packed[i]);
+	}
+	let start = (head + first) / 8;
+	store_bytes(
+		&mut slice[first..first + whole],
+		&bytes[start..start + whole / 8],
+	);
+}
+
 pub trait IntoBitView {
-	type Unsigned: BitView + IsNumber + IsUnsigned;
+	type Unsigned: BitMemory + IsNumber + IsUnsigned;
 	fn into_bitview(self) -> Self::Unsigned;
 }
 macro_rules! impl_into_bitview {
@@ -208,7 +1321,7 @@ macro_rules! impl_into_bitview {
 		impl IntoBitView for $from {
 			type Unsigned = $to;
 			fn into_bitview(self) -> Self::Unsigned {
-				unsafe { std::mem::transmute(self) }
+				self as $to
 			}
 		}
 	};
@@ -218,7 +1331,7 @@ macro_rules! impl_into_bitview_float {
 		impl IntoBitView for $from {
 			type Unsigned = <$from as IsFloat>::Raw;
 			fn into_bitview(self) -> Self::Unsigned {
-				unsafe { std::mem::transmute(self) }
+				self.to_bits()
 			}
 		}
 	};
@@ -226,10 +1339,8 @@ macro_rules! impl_into_bitview_float {
 impl_into_bitview!(i8, u8);
 impl_into_bitview!(i16, u16);
 impl_into_bitview!(i32, u32);
+impl_into_bitview!(i64, u64);
+impl_into_bitview!(i128, u128);
 impl_into_bitview!(isize, usize);
 impl_into_bitview_float!(f32);
-
-#[cfg(target_pointer_width = "64")]
-impl_into_bitview!(i64, u64);
-#[cfg(target_pointer_width = "64")]
 impl_into_bitview_float!(f64);